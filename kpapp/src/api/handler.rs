@@ -0,0 +1,195 @@
+use std::collections::{BTreeMap, HashSet};
+use std::convert::Infallible;
+use axum::extract::{Path, Query, State};
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Response};
+use axum::response::sse::{Event, KeepAlive, Sse};
+use axum::Json;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use tokio_stream::{Stream, StreamExt};
+use tokio_stream::wrappers::BroadcastStream;
+use validator::Validate;
+use crate::api::state::{KPAppApiOutput, KPAppApiState};
+use crate::util::module::deserialize::string::map_string_or_number;
+use crate::util::module::resource::{KPAppResourceItem, KPAppResourceMode};
+use crate::util::module::scene::KPAppScene;
+
+pub struct KPAppApiError {
+    status: StatusCode,
+    message: String,
+}
+
+impl KPAppApiError {
+    pub fn new<T: ToString>(status: StatusCode, message: T) -> Self {
+        KPAppApiError { status, message: message.to_string() }
+    }
+}
+
+impl From<anyhow::Error> for KPAppApiError {
+    fn from(err: anyhow::Error) -> Self {
+        KPAppApiError::new(StatusCode::BAD_REQUEST, err)
+    }
+}
+
+impl IntoResponse for KPAppApiError {
+    fn into_response(self) -> Response {
+        (self.status, Json(json!({ "error": self.message }))).into_response()
+    }
+}
+
+type KPAppApiResult<T> = Result<Json<T>, KPAppApiError>;
+
+#[derive(Debug, Serialize)]
+pub struct KPAppApiStatus {
+    pub item: Option<String>,
+    pub position: Option<f64>,
+    pub fps: f64,
+    pub speed: f64,
+    pub mode: KPAppResourceMode,
+    pub playlist_size: usize,
+    pub failures: BTreeMap<String, usize>,
+    pub outputs: Vec<KPAppApiOutput>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct KPAppApiPlaylist {
+    pub mode: KPAppResourceMode,
+    pub current: Option<String>,
+    pub list: Vec<KPAppResourceItem>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct KPAppApiInsert {
+    #[serde(default)]
+    pub after: Option<String>,
+    pub item: KPAppResourceItem,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct KPAppApiSkip {
+    #[serde(default = "default_immediately")]
+    pub immediately: bool,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct KPAppApiJump {
+    pub name: String,
+    #[serde(default = "default_immediately")]
+    pub immediately: bool,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct KPAppApiMode {
+    pub mode: KPAppResourceMode,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct KPAppApiPluginArguments {
+    #[serde(deserialize_with = "map_string_or_number")]
+    pub arguments: BTreeMap<String, String>,
+}
+
+// comma separated lists, an empty filter accepts every event
+#[derive(Debug, Deserialize, Default)]
+pub struct KPAppApiEventFilter {
+    #[serde(default)]
+    pub source: Option<String>,
+    #[serde(default)]
+    pub kind: Option<String>,
+}
+
+impl KPAppApiEventFilter {
+    fn split(value: &Option<String>) -> HashSet<String> {
+        value.iter().flat_map(|value| value.split(',')).map(|value| value.trim().to_string()).filter(|value| !value.is_empty()).collect()
+    }
+
+    fn matcher(&self) -> impl Fn(&Value) -> bool {
+        let sources = Self::split(&self.source);
+        let kinds = Self::split(&self.kind);
+        move |event: &Value| {
+            let field_match = |set: &HashSet<String>, field: &str| {
+                set.is_empty() || event.get(field).and_then(|value| value.as_str()).map_or(false, |value| set.contains(value))
+            };
+            field_match(&sources, "source") && field_match(&kinds, "kind")
+        }
+    }
+}
+
+fn default_immediately() -> bool { true }
+
+pub async fn get_status(State(state): State<KPAppApiState>) -> KPAppApiResult<KPAppApiStatus> {
+    let progress = state.controller.get_progress()?;
+    Ok(Json(KPAppApiStatus {
+        item: progress.item,
+        position: progress.position.map(|position| position.as_secs_f64()),
+        fps: progress.fps,
+        speed: progress.speed,
+        mode: state.controller.get_mode()?,
+        playlist_size: state.controller.get_list()?.len(),
+        failures: state.controller.get_failures()?,
+        outputs: state.get_outputs()?,
+    }))
+}
+
+pub async fn get_playlist(State(state): State<KPAppApiState>) -> KPAppApiResult<KPAppApiPlaylist> {
+    Ok(Json(KPAppApiPlaylist {
+        mode: state.controller.get_mode()?,
+        current: state.controller.get_current()?.map(|item| item.name),
+        list: state.controller.get_list()?,
+    }))
+}
+
+pub async fn insert_item(State(state): State<KPAppApiState>, Json(request): Json<KPAppApiInsert>) -> KPAppApiResult<KPAppResourceItem> {
+    request.item.validate().map_err(|err| KPAppApiError::new(StatusCode::BAD_REQUEST, format!("validate item failed. error: {}", err)))?;
+    state.controller.insert_after(request.after, request.item.clone())?;
+    Ok(Json(request.item))
+}
+
+pub async fn remove_item(State(state): State<KPAppApiState>, Path(name): Path<String>) -> KPAppApiResult<KPAppResourceItem> {
+    let item = state.controller.remove(&name).map_err(|err| KPAppApiError::new(StatusCode::NOT_FOUND, err))?;
+    Ok(Json(item))
+}
+
+pub async fn set_mode(State(state): State<KPAppApiState>, Json(request): Json<KPAppApiMode>) -> KPAppApiResult<KPAppResourceMode> {
+    state.controller.set_mode(request.mode.clone())?;
+    Ok(Json(request.mode))
+}
+
+pub async fn next_item(State(state): State<KPAppApiState>, request: Option<Json<KPAppApiSkip>>) -> KPAppApiResult<KPAppApiPlaylist> {
+    let immediately = request.map_or(true, |Json(request)| request.immediately);
+    state.controller.next(immediately)?;
+    get_playlist(State(state)).await
+}
+
+pub async fn previous_item(State(state): State<KPAppApiState>, request: Option<Json<KPAppApiSkip>>) -> KPAppApiResult<KPAppApiPlaylist> {
+    let immediately = request.map_or(true, |Json(request)| request.immediately);
+    state.controller.previous(immediately)?;
+    get_playlist(State(state)).await
+}
+
+pub async fn jump_item(State(state): State<KPAppApiState>, Json(request): Json<KPAppApiJump>) -> KPAppApiResult<KPAppApiPlaylist> {
+    state.controller.jump(&request.name, request.immediately).map_err(|err| KPAppApiError::new(StatusCode::NOT_FOUND, err))?;
+    get_playlist(State(state)).await
+}
+
+pub async fn get_scene(State(state): State<KPAppApiState>) -> KPAppApiResult<KPAppScene> {
+    Ok(Json(state.controller.get_scene()?))
+}
+
+pub async fn update_plugin(State(state): State<KPAppApiState>, Path(name): Path<String>, Json(request): Json<KPAppApiPluginArguments>) -> KPAppApiResult<KPAppScene> {
+    let scene = state.controller.update_plugin(&name, request.arguments).map_err(|err| KPAppApiError::new(StatusCode::NOT_FOUND, err))?;
+    Ok(Json(scene))
+}
+
+
+pub async fn get_events(State(state): State<KPAppApiState>, Query(filter): Query<KPAppApiEventFilter>) -> Sse<impl Stream<Item = Result<Event, Infallible>>> {
+    let matcher = filter.matcher();
+    let stream = BroadcastStream::new(state.subscribe_events()).filter_map(move |event| {
+        // lagged subscribers drop the missed events
+        let event = event.ok().filter(|event| matcher(event))?;
+        let kind = event.get("kind").and_then(|kind| kind.as_str()).unwrap_or("unknown").to_string();
+        Some(Ok(Event::default().event(kind).data(event.to_string())))
+    });
+    Sse::new(stream).keep_alive(KeepAlive::default())
+}
\ No newline at end of file
@@ -0,0 +1,68 @@
+use std::net::SocketAddr;
+use anyhow::{anyhow, Result};
+use axum::http::header::CONTENT_TYPE;
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Response};
+use axum::routing::get;
+use axum::Router;
+use log::info;
+use prometheus::{Encoder, TextEncoder};
+use crate::util::module::metrics::KPAppMetrics;
+
+pub struct KPAppMetricsServer {
+    config: KPAppMetrics,
+}
+
+impl KPAppMetricsServer {
+    pub fn new(config: KPAppMetrics) -> Self {
+        // register all series up front so they are exported before the first frame
+        kpcodec::util::metrics::register();
+        kpserver::util::metrics::register();
+        KPAppMetricsServer { config }
+    }
+
+    pub fn router(&self) -> Router {
+        Router::new().route("/metrics", get(get_metrics))
+    }
+
+    pub async fn run(self) -> Result<()> {
+        let address = SocketAddr::new(self.config.address, self.config.port as u16);
+        let listener = tokio::net::TcpListener::bind(address).await.map_err(|err| anyhow!("bind metrics server failed. address: {}, error: {}", address, err))?;
+        info!("metrics server listen on {}", address);
+        axum::serve(listener, self.router()).await.map_err(|err| anyhow!("metrics server exit failed. error: {}", err))
+    }
+}
+
+async fn get_metrics() -> Response {
+    let encoder = TextEncoder::new();
+    let mut buffer = Vec::new();
+    if let Err(err) = encoder.encode(&prometheus::gather(), &mut buffer) {
+        return (StatusCode::INTERNAL_SERVER_ERROR, format!("encode metrics failed. error: {}", err)).into_response();
+    }
+    ([(CONTENT_TYPE, encoder.format_type().to_string())], buffer).into_response()
+}
+
+#[cfg(test)]
+mod tests {
+    use anyhow::Result;
+    use axum::body::Body;
+    use axum::http::{Request, StatusCode};
+    use http_body_util::BodyExt;
+    use tower::ServiceExt;
+    use crate::api::metrics::KPAppMetricsServer;
+    use crate::util::module::metrics::KPAppMetrics;
+
+    #[tokio::test]
+    async fn export_metrics() -> Result<()> {
+        let router = KPAppMetricsServer::new(KPAppMetrics::default()).router();
+        kpserver::util::metrics::METRICS.push_reconnects.with_label_values(&["push"]).inc();
+
+        let response = router.oneshot(Request::builder().uri("/metrics").body(Body::empty())?).await?;
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = String::from_utf8(response.into_body().collect().await?.to_bytes().to_vec())?;
+        assert!(body.contains("kplayer_output_bytes_total"));
+        assert!(body.contains("kplayer_server_publishers"));
+        assert!(body.contains(r#"kplayer_server_push_reconnects_total{name="push"}"#));
+        Ok(())
+    }
+}
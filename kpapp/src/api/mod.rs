@@ -0,0 +1,4 @@
+pub mod state;
+pub mod handler;
+pub mod server;
+pub mod metrics;
@@ -0,0 +1,194 @@
+use std::net::SocketAddr;
+use anyhow::{anyhow, Result};
+use axum::extract::{Request, State};
+use axum::http::header::AUTHORIZATION;
+use axum::http::StatusCode;
+use axum::middleware::{from_fn_with_state, Next};
+use axum::response::{IntoResponse, Response};
+use axum::routing::{delete, get, post, put};
+use axum::Router;
+use log::info;
+use crate::api::handler::*;
+use crate::api::state::KPAppApiState;
+use crate::util::module::api::KPAppApi;
+
+pub struct KPAppApiServer {
+    config: KPAppApi,
+    state: KPAppApiState,
+}
+
+impl KPAppApiServer {
+    pub fn new(config: KPAppApi, state: KPAppApiState) -> Self {
+        KPAppApiServer { config, state }
+    }
+
+    pub fn router(&self) -> Router {
+        Router::new()
+            .route("/status", get(get_status))
+            .route("/playlist", get(get_playlist).post(insert_item))
+            .route("/playlist/mode", put(set_mode))
+            .route("/playlist/next", post(next_item))
+            .route("/playlist/previous", post(previous_item))
+            .route("/playlist/jump", post(jump_item))
+            .route("/playlist/:name", delete(remove_item))
+            .route("/scene", get(get_scene))
+            .route("/scene/:name", put(update_plugin))
+            .route("/events", get(get_events))
+            .layer(from_fn_with_state(self.config.token.clone(), authorize))
+            .with_state(self.state.clone())
+    }
+
+    pub async fn run(self) -> Result<()> {
+        let address = SocketAddr::new(self.config.address, self.config.port as u16);
+        let listener = tokio::net::TcpListener::bind(address).await.map_err(|err| anyhow!("bind api server failed. address: {}, error: {}", address, err))?;
+        info!("api server listen on {}", address);
+        axum::serve(listener, self.router()).await.map_err(|err| anyhow!("api server exit failed. error: {}", err))
+    }
+}
+
+async fn authorize(State(token): State<Option<String>>, request: Request, next: Next) -> Response {
+    if let Some(token) = token {
+        let authorized = request.headers().get(AUTHORIZATION)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.strip_prefix("Bearer "))
+            .map_or(false, |value| value == token);
+        if !authorized {
+            return KPAppApiError::new(StatusCode::UNAUTHORIZED, "invalid or missing api token").into_response();
+        }
+    }
+    next.run(request).await
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+    use anyhow::Result;
+    use axum::body::Body;
+    use axum::http::{Request, StatusCode};
+    use axum::Router;
+    use http_body_util::BodyExt;
+    use kpserver::util::message::KPServerMessage;
+    use serde_json::{json, Value};
+    use tower::ServiceExt;
+    use crate::api::server::KPAppApiServer;
+    use crate::api::state::KPAppApiState;
+    use crate::app::controller::KPAppController;
+    use crate::app::playlist::KPAppPlaylist;
+    use crate::util::config::KPAppConfig;
+    use crate::util::module::api::KPAppApi;
+    use crate::util::module::encode::KPAppEncode;
+    use crate::util::module::failure::KPAppFailure;
+    use crate::util::module::output::KPAppOutput;
+    use crate::util::module::resource::{KPAppResource, KPAppResourceItem, KPAppResourceMode, SingleDetail};
+    use crate::util::module::resource::ResourceItem::Single;
+    use crate::util::module::scene::{KPAppPlugin, KPAppScene};
+    use crate::util::module::server::KPAppServer;
+    use crate::util::module::shutdown::KPAppShutdown;
+
+    fn create_item(name: &str) -> KPAppResourceItem {
+        KPAppResourceItem { name: name.to_string(), resource: Single { single: SingleDetail { path: format!("{}.mp4", name), ..Default::default() } }, scene: None }
+    }
+
+    fn create_server(token: Option<String>) -> (KPAppApiServer, KPAppApiState) {
+        let config = KPAppConfig {
+            playlist: KPAppResource { name: "playlist".to_string(), mode: KPAppResourceMode::Sequence, list: vec![create_item("a"), create_item("b")] },
+            output: vec![KPAppOutput::new("push", "rtmp://127.0.0.1:1935/live/test")],
+            scene: KPAppScene { name: "scene".to_string(), list: vec![KPAppPlugin { name: "text".to_string(), arguments: Default::default() }] },
+            schedule: None,
+            encode: KPAppEncode::default(),
+            failure: KPAppFailure::default(),
+            server: KPAppServer::default(),
+            api: None,
+            metrics: None,
+            shutdown: KPAppShutdown::default(),
+            fallback: None,
+        };
+        let controller = KPAppController::new(KPAppPlaylist::new(&config.playlist), config.scene.clone());
+        let state = KPAppApiState::new(controller, &config);
+        (KPAppApiServer::new(KPAppApi { token, ..Default::default() }, state.clone()), state)
+    }
+
+    async fn request(router: &Router, method: &str, uri: &str, body: Option<&str>) -> Result<(StatusCode, Value)> {
+        let request = Request::builder().method(method).uri(uri).header("content-type", "application/json")
+            .body(body.map_or(Body::empty(), |body| Body::from(body.to_string())))?;
+        let response = router.clone().oneshot(request).await?;
+        let status = response.status();
+        let bytes = response.into_body().collect().await?.to_bytes();
+        Ok((status, serde_json::from_slice(&bytes).unwrap_or(Value::Null)))
+    }
+
+    #[tokio::test]
+    async fn playlist_and_scene() -> Result<()> {
+        let (server, state) = create_server(None);
+        let router = server.router();
+
+        state.on_server_message(&KPServerMessage::RtmpPushStart { name: "push".to_string(), sink_url: "rtmp://127.0.0.1:1935/live/test".to_string(), retry_interval: None, retry_count: None })?;
+        let (status, body) = request(&router, "GET", "/status", None).await?;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["playlist_size"], 2);
+        assert_eq!(body["outputs"][0]["state"], "pushing");
+        assert_eq!(body["outputs"][0]["sink_url"], "rtmp://127.0.0.1:1935/******");
+
+        let (status, body) = request(&router, "POST", "/playlist", Some(r#"{"after": "a", "item": {"name": "live", "resource": {"Network": {"network": {"url": "rtmp://127.0.0.1/live/source"}}}}}"#)).await?;
+        assert_eq!(status, StatusCode::OK, "{}", body);
+        let (_, body) = request(&router, "GET", "/playlist", None).await?;
+        assert_eq!(body["list"][1]["name"], "live");
+
+        let (status, _) = request(&router, "POST", "/playlist", Some(r#"{"item": {"name": "bad", "resource": {"Network": {"network": {"url": "ftp://127.0.0.1/source"}}}}}"#)).await?;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+
+        let (status, _) = request(&router, "DELETE", "/playlist/live", None).await?;
+        assert_eq!(status, StatusCode::OK);
+        let (status, _) = request(&router, "DELETE", "/playlist/live", None).await?;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+
+        let (status, _) = request(&router, "PUT", "/playlist/mode", Some(r#"{"mode": "repeat_all"}"#)).await?;
+        assert_eq!(status, StatusCode::OK);
+        let (status, _) = request(&router, "POST", "/playlist/jump", Some(r#"{"name": "b"}"#)).await?;
+        assert_eq!(status, StatusCode::OK);
+        assert!(state.controller.lock()?.has_pending());
+        let (status, _) = request(&router, "POST", "/playlist/next", None).await?;
+        assert_eq!(status, StatusCode::OK);
+
+        let (status, body) = request(&router, "PUT", "/scene/text", Some(r#"{"arguments": {"text": "changed", "size": 24}}"#)).await?;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["list"][0]["arguments"]["size"], "24");
+        assert!(state.controller.take_scene()?.is_some());
+        let (status, _) = request(&router, "PUT", "/scene/missing", Some(r#"{"arguments": {}}"#)).await?;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn event_stream() -> Result<()> {
+        let (server, state) = create_server(None);
+        let request = Request::builder().uri("/events?source=transcode&kind=item_started,item_finished").body(Body::empty())?;
+        let response = server.router().oneshot(request).await?;
+        assert_eq!(response.status(), StatusCode::OK);
+
+        state.publish_event(json!({"source": "server", "kind": "item_started", "name": "core"}));
+        state.publish_event(json!({"source": "transcode", "kind": "progress", "name": "a"}));
+        state.publish_event(json!({"source": "transcode", "kind": "item_started", "name": "a"}));
+
+        let mut body = response.into_body();
+        let frame = tokio::time::timeout(Duration::from_secs(5), body.frame()).await?.unwrap()?;
+        let data = String::from_utf8(frame.into_data().unwrap().to_vec())?;
+        assert!(data.starts_with("event: item_started\n"), "{}", data);
+        assert!(data.contains(r#""name":"a""#), "{}", data);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn authorize_token() -> Result<()> {
+        let (server, _) = create_server(Some("secret".to_string()));
+        let router = server.router();
+
+        let (status, _) = request(&router, "GET", "/status", None).await?;
+        assert_eq!(status, StatusCode::UNAUTHORIZED);
+
+        let request = Request::builder().uri("/status").header("authorization", "Bearer secret").body(Body::empty())?;
+        let response = router.oneshot(request).await?;
+        assert_eq!(response.status(), StatusCode::OK);
+        Ok(())
+    }
+}
@@ -0,0 +1,84 @@
+use std::collections::BTreeMap;
+use std::sync::{Arc, Mutex, MutexGuard};
+use anyhow::{anyhow, Result};
+use kpserver::util::message::KPServerMessage;
+use kpserver::util::secret::redact_url;
+use serde::Serialize;
+use serde_json::Value;
+use tokio::sync::broadcast;
+use crate::app::controller::KPAppController;
+use crate::util::config::KPAppConfig;
+
+const EVENT_CHANNEL_SIZE: usize = 100;
+
+#[derive(Clone, Debug, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum KPAppApiOutputState {
+    Waiting,
+    Pushing,
+    Stopped,
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct KPAppApiOutput {
+    pub name: String,
+    pub sink_url: String,
+    pub state: KPAppApiOutputState,
+    pub error: Option<String>,
+}
+
+#[derive(Clone)]
+pub struct KPAppApiState {
+    pub(crate) controller: KPAppController,
+    outputs: Arc<Mutex<BTreeMap<String, KPAppApiOutput>>>,
+    events: broadcast::Sender<Value>,
+}
+
+impl KPAppApiState {
+    pub fn new(controller: KPAppController, config: &KPAppConfig) -> Self {
+        let outputs = config.output.iter().map(|output| (output.name.clone(), KPAppApiOutput {
+            name: output.name.clone(),
+            sink_url: redact_url(&output.path),
+            state: KPAppApiOutputState::Waiting,
+            error: None,
+        })).collect();
+        let (events, _) = broadcast::channel(EVENT_CHANNEL_SIZE);
+        KPAppApiState {
+            controller,
+            outputs: Arc::new(Mutex::new(outputs)),
+            events,
+        }
+    }
+
+    // events are json objects carrying a "source" and a "kind" field
+    pub fn publish_event(&self, event: Value) {
+        // no subscriber is connected
+        let _ = self.events.send(event);
+    }
+
+    pub fn subscribe_events(&self) -> broadcast::Receiver<Value> {
+        self.events.subscribe()
+    }
+
+    // track the push state of every output from the server events
+    pub fn on_server_message(&self, message: &KPServerMessage) -> Result<()> {
+        let (name, state, error) = match message {
+            KPServerMessage::RtmpPushStart { name, .. } => (name, KPAppApiOutputState::Pushing, None),
+            KPServerMessage::RtmpPushStop { name, error, .. } => (name, KPAppApiOutputState::Stopped, error.clone()),
+            _ => return Ok(()),
+        };
+        if let Some(output) = self.lock_outputs()?.get_mut(name) {
+            output.state = state;
+            output.error = error;
+        }
+        Ok(())
+    }
+
+    pub fn get_outputs(&self) -> Result<Vec<KPAppApiOutput>> {
+        Ok(self.lock_outputs()?.values().cloned().collect())
+    }
+
+    fn lock_outputs(&self) -> Result<MutexGuard<BTreeMap<String, KPAppApiOutput>>> {
+        self.outputs.lock().map_err(|err| anyhow!("lock outputs failed. error: {}", err))
+    }
+}
@@ -4,129 +4,645 @@ use std::panic::PanicHookInfo;
 use crate::util::context::KPAppContext;
 use anyhow::{anyhow, Result};
 use kpcodec::decode::decode::KPDecode;
+use kpcodec::decode::mix::KPMixCodec;
 use kpcodec::filter::graph::{KPGraph, KPGraphStatus};
 use kpcodec::util::alias::KPAVMediaType;
 use kpcodec::util::encode_parameter::KPEncodeParameter;
 use kpscene::scene::engine::wasm::KPEngine;
 use kpscene::scene::scene::{KPScene, KPSceneSortType};
-use crate::util::module::resource::ResourceItem;
+use crate::util::module::resource::{KPAppResourceItem, KPAppResourceMode, MixEndWith, ResourceItem};
+use crate::util::module::scene::{KPAppScene, KPAppSceneOverride};
+use crate::util::module::failure::KPAppFailurePolicy;
+use crate::util::module::fallback::FALLBACK_INPUT_FORMAT;
 use std::path::PathBuf;
-use log::{debug, info};
+use log::{debug, error, info, warn};
+use std::time::{Duration, Instant};
 use kpcodec::encode::encode::KPEncode;
 use kpcodec::encode::linker::KPLinker;
 use kpcodec::util::codec_status::KPCodecStatus;
 use kpscene::scene::graph::KPSceneGraph;
 use crate::init::initialize;
 use crate::util::vars::KPAppStatus;
+use crate::app::controller::{KPAppController, KPAppProgress};
+use crate::app::playlist::KPAppPlaylist;
+use crate::app::source::KPAppSource;
+use crate::app::schedule::{KPAppClock, KPAppScheduler, KPAppSystemClock};
+use crate::app::state::KPAppState;
+use crate::notify::notifier::KPAppNotifier;
+use crate::notify::log_notifier::KPAppLogNotifier;
+use crate::util::message::KPAppMessage;
+use std::sync::Arc;
+
+const RECONNECT_CHECK_INTERVAL: Duration = Duration::from_millis(100);
+const SCHEDULE_CHECK_INTERVAL: Duration = Duration::from_millis(500);
+const STATE_SAVE_INTERVAL: Duration = Duration::from_secs(5);
+const PROGRESS_INTERVAL: Duration = Duration::from_secs(1);
+const FALLBACK_NAME: &str = "fallback";
+
+// when the fallback slate hands back to the playlist
+enum KPAppFallbackUntil {
+    Deadline(Instant),
+    Playable,
+}
 
 pub struct KPApp {
     context: KPAppContext,
     encode_parameter: BTreeMap<KPAVMediaType, KPEncodeParameter>,
     linker: KPLinker,
+    controller: KPAppController,
+    scheduler: Option<KPAppScheduler>,
+    notifier: Arc<dyn KPAppNotifier>,
 
     // options
     output_format: String,
+    state_path: PathBuf,
 
     // state
     status: KPAppStatus,
+    state: Option<KPAppState>,
+    resume: Option<KPAppState>,
+    scene_override: Option<KPAppSceneOverride>,
+    fallback: Option<KPAppFallbackUntil>,
+    wrap_count: usize,
+    video_frame_count: usize,
 }
 
 impl KPApp {
-    pub fn new(context: KPAppContext, encode_parameter: BTreeMap<KPAVMediaType, KPEncodeParameter>) -> Result<Self> {
+    pub fn new(context: KPAppContext, encode_parameter: BTreeMap<KPAVMediaType, KPEncodeParameter>, notifier: Arc<dyn KPAppNotifier>) -> Result<Self> {
         let output_format = "flv".to_string();
-        let output_path = context.config.output.path.clone();
+        let output_path = context.get_temporarily_server_url();
 
         let linker = KPLinker::new(output_format.clone(), encode_parameter.clone(), output_path)?;
+
+        // gaps between schedule entries play the fallback, the main playlist by default
+        let scheduler = match &context.config.schedule {
+            Some(schedule) => {
+                let fallback = schedule.fallback.clone().unwrap_or(context.config.playlist.clone());
+                Some(KPAppScheduler::new(schedule, fallback, Arc::new(KPAppSystemClock))?)
+            }
+            None => None,
+        };
+        let playlist = match &scheduler {
+            Some(scheduler) => KPAppPlaylist::new(scheduler.get_fallback()),
+            None => KPAppPlaylist::new(&context.config.playlist),
+        };
+        let controller = KPAppController::new(playlist, context.config.scene.clone());
+
+        // resume from the saved state
+        let state_path = KPAppState::get_path(&context.home_dir);
+        let resume = match context.ignore_state {
+            true => {
+                info!("ignore saved playback state. path: {}", state_path.display());
+                None
+            }
+            false => Self::load_state(&state_path, &controller),
+        };
+
         Ok(KPApp {
             context,
             encode_parameter,
             output_format,
+            state_path,
             status: KPAppStatus::None,
+            state: None,
+            resume,
+            scene_override: None,
+            fallback: None,
+            wrap_count: 0,
+            video_frame_count: 0,
             linker,
+            controller,
+            scheduler,
+            notifier,
         })
     }
 
+    fn load_state(state_path: &PathBuf, controller: &KPAppController) -> Option<KPAppState> {
+        let state = match KPAppState::load(state_path) {
+            Ok(state) => state?,
+            Err(err) => {
+                warn!("load playback state failed. error: {}", err);
+                return None;
+            }
+        };
+        let result = controller.lock().and_then(|mut playlist| playlist.resume(&state.item, state.child.clone()));
+        if let Err(err) = result {
+            warn!("saved playback item is not in playlist. name: {}, error: {}", state.item, err);
+            return None;
+        }
+        info!("resume playback state. name: {}, position: {:?}", state.get_name(), state.position);
+        Some(state)
+    }
+
+    // persist the current item and position, called periodically and on shutdown
+    pub fn save_state(&self) -> Result<()> {
+        if let Some(state) = &self.state {
+            state.save(&self.state_path)?;
+            debug!("save playback state. name: {}, position: {:?}", state.get_name(), state.position);
+        }
+        Ok(())
+    }
+
+    pub fn get_controller(&self) -> KPAppController {
+        self.controller.clone()
+    }
+
+    pub fn set_schedule_clock(&mut self, clock: Arc<dyn KPAppClock>) {
+        if let Some(scheduler) = self.scheduler.as_mut() {
+            scheduler.set_clock(clock);
+        }
+    }
+
     pub async fn start(&mut self) -> Result<()> {
-        assert_eq!(self.status, KPAppStatus::None);
+        let result = self.start_playlist().await;
+
+        // write the output trailer before the servers stop
+        if let Err(err) = self.linker.close() {
+            error!("close output linker failed. error: {}", err);
+        }
+        self.notifier.notify(&KPAppMessage::AppClosed { error: result.as_ref().err().map(|err| err.to_string()) }).await;
+        match &result {
+            // the playlist is finished, the next start begins again
+            Ok(_) if !self.controller.is_shutdown() => KPAppState::remove(&self.state_path)?,
+            _ => self.save_state()?,
+        }
+        result
+    }
 
-        let cfg = self.context.config.clone();
+    async fn start_playlist(&mut self) -> Result<()> {
+        assert_eq!(self.status, KPAppStatus::None);
+        let failure = self.context.config.failure.clone();
+        let mut consecutive_failures = 0;
 
         // start playlist
-        for item in cfg.playlist.list.iter() {
-            assert!(matches!(self.status, KPAppStatus::None | KPAppStatus::Ended));
-
-            // create decode
-            let mut decode = match &item.resource {
-                ResourceItem::Single { single } => {
-                    let mut decode = KPDecode::new(single.path.clone());
-                    decode.set_expect_stream(single.expect_streams.clone());
-                    decode
+        loop {
+            if self.controller.is_shutdown() {
+                info!("playlist stopped by shutdown");
+                break;
+            }
+            let item = match self.next_item().await? {
+                Some(item) => item,
+                None if self.play_fallback(KPAppFallbackUntil::Playable).await? => continue,
+                None => break,
+            };
+            info!("playlist item start. name: {}", item.name);
+            if self.controller.lock()?.take_wrapped() {
+                self.wrap_count += 1;
+                self.notifier.notify(&KPAppMessage::PlaylistWrapped { count: self.wrap_count }).await;
+            }
+
+            let mut attempt = 0;
+            loop {
+                assert!(matches!(self.status, KPAppStatus::None | KPAppStatus::Ended));
+                let start_time = Instant::now();
+                let result = self.play_item(&item).await;
+
+                // set linker ascent
+                self.linker.gradient_ascent();
+                if self.controller.is_shutdown() {
+                    break;
+                }
+
+                let mut err = match result {
+                    Ok(_) => {
+                        consecutive_failures = 0;
+                        self.controller.set_progress(KPAppProgress::default())?;
+                        self.notifier.notify(&KPAppMessage::ItemFinished { name: item.name.clone(), duration: start_time.elapsed() }).await;
+                        break;
+                    }
+                    Err(err) => err,
+                };
+                self.status = KPAppStatus::Ended;
+                self.controller.set_progress(KPAppProgress::default())?;
+                self.notifier.notify(&KPAppMessage::ItemFailed { name: item.name.clone(), error: err.to_string() }).await;
+
+                // a network input that stayed up long enough counts as reconnected, the backoff starts over
+                if let ResourceItem::Network { network } = &item.resource {
+                    attempt = network.reconnect.next_attempt(attempt, start_time.elapsed());
                 }
+
+                // reconnect network input with backoff, retry other input by the failure policy
+                let retry_interval = match &item.resource {
+                    ResourceItem::Network { network } if attempt < network.reconnect.max_retries => {
+                        Some(network.reconnect.get_interval(attempt))
+                    }
+                    ResourceItem::Network { .. } => {
+                        err = anyhow!("network input reconnect failed. name: {}, attempt: {}, error: {}", item.name, attempt, err);
+                        None
+                    }
+                    _ if failure.policy == KPAppFailurePolicy::Retry && attempt < failure.retry_count => Some(failure.retry_interval),
+                    _ => None,
+                };
+                if let Some(interval) = retry_interval {
+                    attempt += 1;
+                    warn!("playlist item failed, retrying. name: {}, attempt: {}, interval: {:?}, error: {}", item.name, attempt, interval, err);
+                    if !self.wait_reconnect(interval).await? {
+                        info!("playlist changed while retrying. name: {}", item.name);
+                        break;
+                    }
+                    continue;
+                }
+
+                if failure.policy == KPAppFailurePolicy::Abort {
+                    return Err(err);
+                }
+
+                // skip the broken item
+                let failures = self.controller.add_failure(&item.name)?;
+                warn!("playlist item skipped. name: {}, failures: {}, error: {}", item.name, failures, err);
+                self.notifier.notify(&KPAppMessage::ItemSkipped { name: item.name.clone(), failures, error: err.to_string() }).await;
+
+                // every item failed in a row, wait before the next round
+                consecutive_failures += 1;
+                if consecutive_failures >= std::cmp::max(self.controller.get_list()?.len(), 1) {
+                    warn!("every playlist item failed, waiting. interval: {:?}", failure.retry_interval);
+                    consecutive_failures = 0;
+                    self.wait_reconnect(failure.retry_interval).await?;
+                }
+                break;
+            }
+        }
+
+        self.status = KPAppStatus::Closed;
+        Ok(())
+    }
+
+    async fn next_item(&mut self) -> Result<Option<KPAppResourceItem>> {
+        let mut fallback_loaded = false;
+        loop {
+            if self.controller.is_shutdown() {
+                return Ok(None);
+            }
+
+            // switch to the fired schedule entry at the item boundary
+            if let Some(scheduler) = self.scheduler.as_mut() {
+                scheduler.poll();
+                if let Some(entry) = scheduler.take_pending() {
+                    info!("schedule entry start. name: {}, policy: {:?}", entry.name, entry.policy);
+                    self.controller.load(&entry.to_resource())?;
+                }
+            }
+
+            if let Some(item) = self.controller.lock()?.next_item() {
+                return Ok(Some(item));
+            }
+
+            // fill the gap until the next schedule entry
+            let scheduler = match self.scheduler.as_ref() {
+                Some(scheduler) => scheduler,
+                None => return Ok(None),
             };
-            decode.open().map_err(|err| anyhow!("open input media file failed. path: {:?}, error: {}", item.resource, err))?;
-            decode.find_streams()?;
-            decode.open_codec()?;
-            decode.stream_to_codec()?;
-            debug!("create decode success");
+            if !fallback_loaded {
+                info!("schedule gap, play fallback. name: {}", scheduler.get_fallback().name);
+                self.controller.load(scheduler.get_fallback())?;
+                fallback_loaded = true;
+            } else if self.context.config.fallback.is_some() {
+                // the slate covers the rest of the gap
+                return Ok(None);
+            } else {
+                tokio::time::sleep(SCHEDULE_CHECK_INTERVAL).await;
+            }
+        }
+    }
+
+    // returns true once a schedule entry fired, hard cut entries interrupt the current item
+    fn poll_schedule(&mut self, hard_cut_only: bool) -> bool {
+        match self.scheduler.as_mut() {
+            Some(scheduler) => {
+                scheduler.poll();
+                scheduler.has_pending() && (!hard_cut_only || scheduler.is_hard_cut())
+            }
+            None => false,
+        }
+    }
+
+    async fn play_item(&mut self, item: &KPAppResourceItem) -> Result<()> {
+        self.scene_override = item.scene.clone();
+
+        // create decode
+        let resume_point = match self.resume.take() {
+            Some(state) if state.get_name() == item.name => Some(state.position),
+            _ => None,
+        };
+        let mut decode = self.create_source(item, resume_point)?;
+        if self.controller.lock()?.get_mode() == &KPAppResourceMode::RepeatOne {
+            decode.set_enable_loop(true);
+        }
+        decode.open().map_err(|err| anyhow!("open input media file failed. path: {:?}, error: {}", item.resource, err))?;
+        debug!("create decode success");
+        self.notifier.notify(&KPAppMessage::ItemOpened { name: item.name.clone() }).await;
+
+        let (scene, graph_map, encode) = self.create_chain(&decode).await?;
+
+        // transcode
+        let (parent, child) = self.controller.lock()?.get_current_position().unwrap_or((item.name.clone(), None));
+        self.state = Some(KPAppState::new(parent, child, resume_point.unwrap_or_default()));
+        self.status = KPAppStatus::Initialized;
+        self.controller.set_progress(KPAppProgress { item: Some(item.name.clone()), position: resume_point, ..Default::default() })?;
+        self.notifier.notify(&KPAppMessage::ItemStarted { name: item.name.clone() }).await;
+        self.transcode(&item.name, decode, &scene, graph_map, encode).await?;
 
-            // get decode expect streams
-            let expect_streams = decode.get_expect_streams();
+        Ok(())
+    }
+
+    // the slate is a lavfi input, so it shares the scene, encode and linker chain with playlist items
+    async fn play_fallback(&mut self, until: KPAppFallbackUntil) -> Result<bool> {
+        let fallback = match &self.context.config.fallback {
+            Some(fallback) => fallback.clone(),
+            None => return Ok(false),
+        };
+        if self.controller.is_shutdown() {
+            return Ok(false);
+        }
 
-            // load scene
-            let mut scene = KPScene::new();
-            for scene_item in cfg.scene.list.iter() {
-                let plugin_path = self.context.plugin_sub_path.join(scene_item.name.clone() + &self.context.plugin_extension);
-                debug!("load plugin path: {:?}", plugin_path);
-                scene.add_engine(scene_item.name.clone(), KPEngine::new_with_file(plugin_path, scene_item.arguments.clone()).await?);
+        let mut decode = KPDecode::new(fallback.to_filter_graph(&self.context.config.encode));
+        decode.set_input_format(FALLBACK_INPUT_FORMAT);
+        let mut decode = KPAppSource::Single(decode);
+        if let Err(err) = decode.open() {
+            warn!("open fallback slate failed. path: {}, error: {}", fallback.path, err);
+            return Ok(false);
+        }
+
+        self.scene_override = fallback.scene.clone();
+        let (scene, graph_map, encode) = match self.create_chain(&decode).await {
+            Ok(chain) => chain,
+            Err(err) => {
+                warn!("create fallback slate failed. path: {}, error: {}", fallback.path, err);
+                return Ok(false);
             }
-            debug!("load scene success");
+        };
+
+        // no playback state, a restart resumes the playlist item
+        info!("fallback slate start. path: {}", fallback.path);
+        self.state = None;
+        self.status = KPAppStatus::Initialized;
+        self.fallback = Some(until);
+        self.controller.set_progress(KPAppProgress { item: Some(FALLBACK_NAME.to_string()), ..Default::default() })?;
+        self.notifier.notify(&KPAppMessage::FallbackStarted { path: fallback.path.clone() }).await;
+        let start_time = Instant::now();
+        let result = self.transcode(&FALLBACK_NAME.to_string(), decode, &scene, graph_map, encode).await;
+        self.fallback = None;
+        self.linker.gradient_ascent();
+        self.status = KPAppStatus::None;
+        self.controller.set_progress(KPAppProgress::default())?;
+        self.notifier.notify(&KPAppMessage::FallbackStopped { duration: start_time.elapsed() }).await;
+
+        match result {
+            Ok(_) => Ok(true),
+            Err(err) => {
+                warn!("fallback slate failed. path: {}, error: {}", fallback.path, err);
+                Ok(false)
+            }
+        }
+    }
+
+    async fn create_chain(&self, decode: &KPAppSource) -> Result<(KPScene, HashMap<KPAVMediaType, KPGraph>, KPEncode)> {
+        let scene_cfg = self.get_item_scene(&self.context.config.scene);
+
+        // get decode expect streams
+        let expect_streams = decode.get_expect_streams();
+
+        // load scene
+        let mut scene = KPScene::new();
+        for scene_item in scene_cfg.list.iter() {
+            let plugin_path = self.context.plugin_sub_path.join(scene_item.name.clone() + &self.context.plugin_extension);
+            debug!("load plugin path: {:?}", plugin_path);
+            scene.add_engine(scene_item.name.clone(), KPEngine::new_with_file(plugin_path, scene_item.arguments.clone()).await?);
+        }
+        debug!("load scene success");
+
+        let mut graph_map = HashMap::new();
+        for (media_type, _) in expect_streams.iter() {
+            let mut graph = KPGraph::new(media_type);
+            graph.injection_source(decode)?;
 
-            let mut graph_map = HashMap::new();
-            for (media_type, _) in expect_streams.iter() {
-                let mut graph = KPGraph::new(media_type);
-                graph.injection_source(&decode)?;
+            // add before scene
+            graph.add_scene(&scene, KPSceneSortType::Before)?;
 
-                // add before scene
-                graph.add_scene(&scene, KPSceneSortType::Before)?;
+            // add core
+            graph.add_core(media_type, &self.encode_parameter)?;
 
-                // add core
-                graph.add_core(media_type, &self.encode_parameter)?;
+            // add after scene
+            graph.add_scene(&scene, KPSceneSortType::After)?;
+
+            graph.injection_sink()?;
+            graph_map.insert(media_type.clone(), graph);
+        }
 
-                // add after scene
-                graph.add_scene(&scene, KPSceneSortType::After)?;
+        let mut encode = KPEncode::new("flv", self.encode_parameter.clone());
+        encode.enable_sync_timestamp(Some(self.linker.get_output_path()));
+        encode.open()?;
+        encode.write_header()?;
 
-                graph.injection_sink()?;
-                graph_map.insert(media_type.clone(), graph);
+        // set frame size
+        if let Some(audio_graph) = graph_map.get_mut(&KPAVMediaType::KPAVMEDIA_TYPE_AUDIO) {
+            audio_graph.set_frame_size(encode.get_audio_frame_size()?)?;
+        }
+
+        Ok((scene, graph_map, encode))
+    }
+
+    // the slate stops at its deadline, on schedule or navigation, or once the playlist has an item again
+    fn fallback_finished(&mut self) -> Result<bool> {
+        let deadline = match &self.fallback {
+            Some(KPAppFallbackUntil::Deadline(deadline)) => Some(*deadline),
+            Some(KPAppFallbackUntil::Playable) => None,
+            None => return Ok(false),
+        };
+        if self.poll_schedule(false) {
+            return Ok(true);
+        }
+
+        let playlist = self.controller.lock()?;
+        match deadline {
+            Some(deadline) => Ok(Instant::now() >= deadline || playlist.has_pending()),
+            None => Ok(playlist.has_playable()),
+        }
+    }
+
+    // returns false once playlist navigation or shutdown is requested during the wait
+    async fn wait_reconnect(&mut self, interval: Duration) -> Result<bool> {
+        let deadline = Instant::now() + interval;
+
+        // keep the output on air with the slate, sleep out the rest if it stops early
+        self.play_fallback(KPAppFallbackUntil::Deadline(deadline)).await?;
+        while Instant::now() < deadline {
+            if self.controller.is_shutdown() || self.controller.lock()?.has_pending() || self.poll_schedule(false) {
+                return Ok(false);
             }
+            tokio::time::sleep(std::cmp::min(RECONNECT_CHECK_INTERVAL, deadline.saturating_duration_since(Instant::now()))).await;
+        }
+        Ok(true)
+    }
 
-            let mut encode = KPEncode::new("flv", self.encode_parameter.clone());
-            encode.enable_sync_timestamp(Some(self.linker.get_output_path()));
-            encode.open()?;
-            encode.write_header()?;
+    fn create_source(&self, item: &KPAppResourceItem, resume_point: Option<Duration>) -> Result<KPAppSource> {
+        let source = match &item.resource {
+            ResourceItem::Single { single } => {
+                // only the first pass continues from the resume point
+                let resume_point = resume_point.filter(|resume_point| single.get_end_point().map_or(true, |end_point| *resume_point < end_point));
+                if let Some(resume_point) = resume_point {
+                    info!("resume playlist item. name: {}, position: {:?}", item.name, resume_point);
+                }
 
-            // set frame size
-            if let Some(audio_graph) = graph_map.get_mut(&KPAVMediaType::KPAVMEDIA_TYPE_AUDIO) {
-                audio_graph.set_frame_size(encode.get_audio_frame_size()?)?;
+                let mut decode = KPDecode::new(single.path.clone());
+                decode.set_expect_stream(single.expect_streams.clone());
+                decode.set_start_point(single.start);
+                decode.set_resume_point(resume_point.map(|resume_point| std::cmp::max(resume_point, single.start.unwrap_or_default())));
+                decode.set_end_point(single.get_end_point());
+                KPAppSource::Single(decode)
+            }
+            ResourceItem::Mix { mix } => {
+                let maintainer_source = match mix.end_with {
+                    MixEndWith::Longest => None,
+                    MixEndWith::Video => Some(KPAVMediaType::KPAVMEDIA_TYPE_VIDEO),
+                    MixEndWith::Audio => Some(KPAVMediaType::KPAVMEDIA_TYPE_AUDIO),
+                };
+                let mut mix_codec = KPMixCodec::new(mix.video.clone(), mix.audio.clone(), maintainer_source);
+                mix_codec.set_expect_stream(mix.expect_streams.clone());
+                KPAppSource::Mix(mix_codec)
+            }
+            ResourceItem::Network { network } => {
+                let mut decode = KPDecode::new(network.url.clone());
+                decode.set_expect_stream(network.expect_streams.clone());
+                decode.set_timeout(network.open_timeout, network.read_timeout);
+                KPAppSource::Single(decode)
             }
+            ResourceItem::Directory { .. } => {
+                return Err(anyhow!("directory resource must be expanded by playlist. name: {}", item.name));
+            }
+            ResourceItem::Playlist { .. } => {
+                return Err(anyhow!("playlist file resource must be expanded by playlist. name: {}", item.name));
+            }
+        };
+        Ok(source)
+    }
 
-            // transcode
-            self.status = KPAppStatus::Initialized;
-            self.transcode(decode, graph_map, encode)?;
+    // the global scene with the playing item's overrides merged in
+    fn get_item_scene(&self, scene_cfg: &KPAppScene) -> KPAppScene {
+        match &self.scene_override {
+            Some(scene_override) => scene_cfg.merge(scene_override),
+            None => scene_cfg.clone(),
+        }
+    }
 
-            // set linker ascent
-            self.linker.gradient_ascent();
+    // plugin argument changes are sent to the running graph, other scene changes apply at the next item
+    async fn update_scene(&mut self, scene: &KPScene, graph_map: &HashMap<KPAVMediaType, KPGraph>, scene_cfg: KPAppScene) -> Result<()> {
+        let current = self.get_item_scene(&self.context.config.scene);
+        let next = self.get_item_scene(&scene_cfg);
+        let same_plugins = current.list.len() == next.list.len() && current.list.iter().zip(next.list.iter()).all(|(a, b)| a.name == b.name);
+        if !same_plugins {
+            info!("scene plugins changed, apply at next item. name: {}", scene_cfg.name);
+            self.context.config.scene = scene_cfg;
+            return Ok(());
         }
 
-        self.status = KPAppStatus::Closed;
+        for (current_plugin, plugin) in current.list.iter().zip(next.list.iter()) {
+            let arguments: BTreeMap<String, String> = plugin.arguments.iter()
+                .filter(|(key, value)| current_plugin.arguments.get(*key) != Some(*value))
+                .map(|(key, value)| (key.clone(), value.clone()))
+                .collect();
+            if arguments.is_empty() {
+                continue;
+            }
+
+            let command = scene.get_update_argument(&plugin.name, arguments.clone())?;
+            for (_, graph) in graph_map.iter() {
+                graph.send_command(command.clone())?;
+            }
+            info!("scene plugin arguments updated. name: {}, arguments: {:?}", plugin.name, arguments);
+        }
+        self.context.config.scene = scene_cfg;
         Ok(())
     }
 
-    fn transcode(&mut self, mut decode: KPDecode, mut graph_map: HashMap<KPAVMediaType, KPGraph>, mut encode: KPEncode) -> Result<()> {
+    async fn transcode(&mut self, name: &String, mut decode: KPAppSource, scene: &KPScene, mut graph_map: HashMap<KPAVMediaType, KPGraph>, mut encode: KPEncode) -> Result<()> {
         assert_eq!(self.status, KPAppStatus::Initialized);
 
         self.status = KPAppStatus::Starting;
-        for get_frame in decode.iter() {
+        let mut interrupted = false;
+        let mut loop_count = decode.get_loop_count();
+        let mut schedule_check = Instant::now();
+        let mut state_save = Instant::now();
+        let mut progress = (Instant::now(), self.video_frame_count);
+        loop {
+            // progress tick, speed is the encoded frame rate against the configured one
+            let elapsed = progress.0.elapsed();
+            if elapsed >= PROGRESS_INTERVAL {
+                let fps = (self.video_frame_count - progress.1) as f64 / elapsed.as_secs_f64();
+                let framerate = self.context.config.encode.video.framerate as f64;
+                let speed = if framerate > 0.0 { fps / framerate } else { 0.0 };
+                progress = (Instant::now(), self.video_frame_count);
+                let position = decode.get_media_position();
+                self.controller.set_progress(KPAppProgress { item: Some(name.clone()), position, fps, speed })?;
+                self.notifier.notify(&KPAppMessage::Progress { name: name.clone(), position, fps, speed }).await;
+            }
+
+            // save playback state
+            if let (Some(state), Some(position)) = (self.state.as_mut(), decode.get_media_position()) {
+                state.position = position;
+                if state_save.elapsed() >= STATE_SAVE_INTERVAL {
+                    state_save = Instant::now();
+                    if let Err(err) = self.save_state() {
+                        warn!("save playback state failed. error: {}", err);
+                    }
+                }
+            }
+
+            // schedule hard cut
+            if schedule_check.elapsed() >= SCHEDULE_CHECK_INTERVAL {
+                schedule_check = Instant::now();
+                if self.poll_schedule(true) {
+                    info!("playlist item interrupted by schedule");
+                    interrupted = true;
+                    break;
+                }
+                if self.fallback_finished()? {
+                    info!("fallback slate finished");
+                    interrupted = true;
+                    break;
+                }
+
+                // reloaded scene
+                if let Some(scene_cfg) = self.controller.take_scene()? {
+                    if let Err(err) = self.update_scene(scene, &graph_map, scene_cfg).await {
+                        warn!("update scene failed. error: {}", err);
+                    }
+                }
+            }
+
+            // stop at a frame boundary so graphs and encoders are flushed below
+            if self.controller.is_shutdown() {
+                info!("playlist item interrupted by shutdown");
+                interrupted = true;
+                break;
+            }
+
+            // skip current item
+            {
+                let mut playlist = self.controller.lock()?;
+                if playlist.take_interrupt() {
+                    info!("playlist item interrupted");
+                    interrupted = true;
+                    break;
+                }
+
+                // a looping item reaches its boundary at each wrap, checked before the next frame is pulled
+                if decode.get_loop_count() != loop_count {
+                    loop_count = decode.get_loop_count();
+                    let scheduled = self.scheduler.as_ref().map_or(false, |scheduler| scheduler.has_pending());
+                    if playlist.has_pending() || scheduled || playlist.get_mode() != &KPAppResourceMode::RepeatOne {
+                        info!("playlist item loop boundary reached");
+                        interrupted = true;
+                        break;
+                    }
+                }
+            }
+
             // process frame
+            let get_frame = match decode.next_frame() {
+                Some(get_frame) => get_frame,
+                None => break,
+            };
             let (media_type, frame) = get_frame?;
             debug!("decode frame. pts: {}, media_type: {}", frame.get().pts, media_type);
 
@@ -150,7 +666,9 @@ impl KPApp {
         encode.write_trailer()?;
 
         // validate
-        assert_eq!(decode.get_status(), &KPCodecStatus::Ended);
+        if !interrupted {
+            assert_eq!(decode.get_status(), &KPCodecStatus::Ended);
+        }
         for (_, graph) in graph_map.iter() {
             assert_eq!(graph.get_status(), &KPGraphStatus::Ended);
         }
@@ -164,6 +682,9 @@ impl KPApp {
         for filter_frame in graph.iter() {
             let get_filter_frame = filter_frame?;
             debug!("filter frame. pts: {}", get_filter_frame.get().pts);
+            if media_type == KPAVMediaType::KPAVMEDIA_TYPE_VIDEO {
+                self.video_frame_count += 1;
+            }
 
             encode.stream_to_encode(get_filter_frame, &media_type)?;
 
@@ -191,7 +712,7 @@ async fn test_cmd() -> Result<()> {
     let mut encode_parameter = BTreeMap::new();
     encode_parameter.insert(KPAVMediaType::KPAVMEDIA_TYPE_VIDEO, KPEncodeParameter::default(&KPAVMediaType::KPAVMEDIA_TYPE_VIDEO));
     encode_parameter.insert(KPAVMediaType::KPAVMEDIA_TYPE_AUDIO, KPEncodeParameter::default(&KPAVMediaType::KPAVMEDIA_TYPE_AUDIO));
-    let mut app = KPApp::new(context, encode_parameter)?;
+    let mut app = KPApp::new(context, encode_parameter, Arc::new(KPAppLogNotifier::new()))?;
     app.start().await?;
     Ok(())
 }
\ No newline at end of file
@@ -0,0 +1,178 @@
+use std::collections::BTreeMap;
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
+use anyhow::{anyhow, Result};
+use log::info;
+use crate::app::playlist::KPAppPlaylist;
+use crate::util::module::resource::{KPAppResource, KPAppResourceItem, KPAppResourceMode};
+use crate::util::module::scene::KPAppScene;
+
+#[derive(Clone, Debug, Default)]
+pub struct KPAppProgress {
+    pub item: Option<String>,
+    pub position: Option<Duration>,
+    pub fps: f64,
+    pub speed: f64,
+}
+
+#[derive(Clone)]
+pub struct KPAppController {
+    playlist: Arc<Mutex<KPAppPlaylist>>,
+    scene: Arc<Mutex<Option<KPAppScene>>>,
+    scene_config: Arc<Mutex<KPAppScene>>,
+    failures: Arc<Mutex<BTreeMap<String, usize>>>,
+    progress: Arc<Mutex<KPAppProgress>>,
+    shutdown: Arc<AtomicBool>,
+}
+
+impl KPAppController {
+    pub fn new(playlist: KPAppPlaylist, scene: KPAppScene) -> Self {
+        KPAppController {
+            playlist: Arc::new(Mutex::new(playlist)),
+            scene: Arc::new(Mutex::new(None)),
+            scene_config: Arc::new(Mutex::new(scene)),
+            failures: Arc::new(Mutex::new(BTreeMap::new())),
+            progress: Arc::new(Mutex::new(KPAppProgress::default())),
+            shutdown: Arc::new(AtomicBool::new(false)),
+        }
+    }
+
+    pub fn next(&self, immediately: bool) -> Result<()> {
+        self.lock()?.next(immediately);
+        info!("playlist skip to next item. immediately: {}", immediately);
+        Ok(())
+    }
+
+    pub fn previous(&self, immediately: bool) -> Result<()> {
+        self.lock()?.previous(immediately);
+        info!("playlist skip to previous item. immediately: {}", immediately);
+        Ok(())
+    }
+
+    pub fn jump<T: ToString>(&self, name: T, immediately: bool) -> Result<()> {
+        let name = name.to_string();
+        self.lock()?.jump(&name, immediately)?;
+        info!("playlist jump to item. name: {}, immediately: {}", name, immediately);
+        Ok(())
+    }
+
+    pub fn insert_after(&self, name: Option<String>, item: KPAppResourceItem) -> Result<()> {
+        let item_name = item.name.clone();
+        self.lock()?.insert_after(name.clone(), item)?;
+        info!("playlist insert item. name: {}, after: {:?}", item_name, name);
+        Ok(())
+    }
+
+    pub fn remove<T: ToString>(&self, name: T) -> Result<KPAppResourceItem> {
+        let item = self.lock()?.remove(name)?;
+        info!("playlist remove item. name: {}", item.name);
+        Ok(item)
+    }
+
+    pub fn load(&self, resource: &KPAppResource) -> Result<()> {
+        self.lock()?.load(resource);
+        info!("playlist load. name: {}, size: {}", resource.name, resource.list.len());
+        Ok(())
+    }
+
+    pub fn replace(&self, resource: &KPAppResource) -> Result<()> {
+        self.lock()?.replace(resource);
+        info!("playlist replace. name: {}, size: {}", resource.name, resource.list.len());
+        Ok(())
+    }
+
+    pub fn update_scene(&self, scene: KPAppScene) -> Result<()> {
+        info!("scene update. name: {}, size: {}", scene.name, scene.list.len());
+        *self.lock_scene_config()? = scene.clone();
+        *self.lock_scene()? = Some(scene);
+        Ok(())
+    }
+
+    // merge the arguments into one plugin, the running graph receives them as commands
+    pub fn update_plugin<T: ToString>(&self, name: T, arguments: BTreeMap<String, String>) -> Result<KPAppScene> {
+        let name = name.to_string();
+        let mut scene = self.get_scene()?;
+        let plugin = scene.list.iter_mut().find(|plugin| plugin.name == name).ok_or_else(|| anyhow!("scene plugin not found. name: {}", name))?;
+        plugin.arguments.extend(arguments);
+        self.update_scene(scene.clone())?;
+        Ok(scene)
+    }
+
+    pub fn get_scene(&self) -> Result<KPAppScene> {
+        Ok(self.lock_scene_config()?.clone())
+    }
+
+    pub(crate) fn take_scene(&self) -> Result<Option<KPAppScene>> {
+        Ok(self.lock_scene()?.take())
+    }
+
+    pub fn set_mode(&self, mode: KPAppResourceMode) -> Result<()> {
+        info!("playlist set mode. mode: {:?}", mode);
+        self.lock()?.set_mode(mode);
+        Ok(())
+    }
+
+    pub fn get_mode(&self) -> Result<KPAppResourceMode> {
+        Ok(self.lock()?.get_mode().clone())
+    }
+
+    pub fn get_list(&self) -> Result<Vec<KPAppResourceItem>> {
+        Ok(self.lock()?.get_list().clone())
+    }
+
+    pub fn get_current(&self) -> Result<Option<KPAppResourceItem>> {
+        Ok(self.lock()?.get_current().cloned())
+    }
+
+    // failure count per item name, kept across playlist loads
+    pub fn get_failures(&self) -> Result<BTreeMap<String, usize>> {
+        Ok(self.lock_failures()?.clone())
+    }
+
+    pub fn get_progress(&self) -> Result<KPAppProgress> {
+        Ok(self.lock_progress()?.clone())
+    }
+
+    pub(crate) fn set_progress(&self, progress: KPAppProgress) -> Result<()> {
+        *self.lock_progress()? = progress;
+        Ok(())
+    }
+
+    pub(crate) fn add_failure<T: ToString>(&self, name: T) -> Result<usize> {
+        let mut failures = self.lock_failures()?;
+        let count = failures.entry(name.to_string()).or_insert(0);
+        *count += 1;
+        Ok(*count)
+    }
+
+    // the running item stops at the next frame, then the app closes its output
+    pub fn shutdown(&self) {
+        self.shutdown.store(true, Ordering::SeqCst);
+        info!("app shutdown requested");
+    }
+
+    pub fn is_shutdown(&self) -> bool {
+        self.shutdown.load(Ordering::SeqCst)
+    }
+
+    pub(crate) fn lock(&self) -> Result<MutexGuard<KPAppPlaylist>> {
+        self.playlist.lock().map_err(|err| anyhow!("lock playlist failed. error: {}", err))
+    }
+
+    fn lock_scene(&self) -> Result<MutexGuard<Option<KPAppScene>>> {
+        self.scene.lock().map_err(|err| anyhow!("lock scene failed. error: {}", err))
+    }
+
+    fn lock_scene_config(&self) -> Result<MutexGuard<KPAppScene>> {
+        self.scene_config.lock().map_err(|err| anyhow!("lock scene config failed. error: {}", err))
+    }
+
+    fn lock_progress(&self) -> Result<MutexGuard<KPAppProgress>> {
+        self.progress.lock().map_err(|err| anyhow!("lock progress failed. error: {}", err))
+    }
+
+    fn lock_failures(&self) -> Result<MutexGuard<BTreeMap<String, usize>>> {
+        self.failures.lock().map_err(|err| anyhow!("lock failures failed. error: {}", err))
+    }
+}
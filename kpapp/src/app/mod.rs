@@ -1 +1,7 @@
-pub mod app;
\ No newline at end of file
+pub mod app;
+pub mod playlist;
+pub mod controller;
+pub mod source;
+pub mod schedule;
+pub mod state;
+pub mod reload;
\ No newline at end of file
@@ -0,0 +1,823 @@
+use std::collections::{BTreeMap, HashSet, VecDeque};
+use std::path::PathBuf;
+use anyhow::{anyhow, Result};
+use log::warn;
+use rand::Rng;
+use rand::seq::SliceRandom;
+use crate::util::module::resource::{KPAppResource, KPAppResourceItem, KPAppResourceMode, ResourceItem, SingleDetail};
+
+const HISTORY_LIMIT: usize = 100;
+
+#[derive(Clone, Debug)]
+pub struct KPAppPlaylist {
+    list: Vec<KPAppResourceItem>,
+    mode: KPAppResourceMode,
+
+    // cursor state
+    cursor: usize,
+    current: Option<usize>,
+    pending: Option<usize>,
+    advance: bool,
+    interrupt: bool,
+    wrapped: bool,
+
+    // shuffle state
+    history: Vec<String>,
+    played: HashSet<String>,
+
+    // directory state
+    directory: Option<HashSet<PathBuf>>,
+    playing: Option<KPAppResourceItem>,
+
+    // playlist file state
+    entries: Option<VecDeque<KPAppResourceItem>>,
+
+    // directory file or playlist entry to continue from
+    resume: Option<String>,
+}
+
+impl KPAppPlaylist {
+    pub fn new(resource: &KPAppResource) -> Self {
+        KPAppPlaylist {
+            list: resource.list.clone(),
+            mode: resource.mode.clone(),
+            cursor: 0,
+            current: None,
+            pending: None,
+            advance: false,
+            interrupt: false,
+            wrapped: false,
+            history: Vec::new(),
+            played: HashSet::new(),
+            directory: None,
+            playing: None,
+            entries: None,
+            resume: None,
+        }
+    }
+
+    // replace the list, navigation state starts over
+    pub fn load(&mut self, resource: &KPAppResource) {
+        *self = KPAppPlaylist::new(resource);
+    }
+
+    // swap in a new list, the current item keeps playing and the change applies at the next item
+    pub fn replace(&mut self, resource: &KPAppResource) {
+        let current = self.current.and_then(|index| self.list.get(index)).map(|item| item.name.clone());
+        let pending = self.pending.and_then(|index| self.list.get(index)).map(|item| item.name.clone());
+        let following: Vec<String> = self.list.iter().skip(self.cursor).map(|item| item.name.clone()).collect();
+        let previous: HashSet<String> = self.list.iter().map(|item| item.name.clone()).collect();
+
+        self.list = resource.list.clone();
+        if self.mode != resource.mode {
+            self.set_mode(resource.mode.clone());
+        }
+        let removed = current.is_some();
+        self.current = current.and_then(|name| self.position(name).ok());
+        self.pending = pending.and_then(|name| self.position(name).ok());
+        self.cursor = match self.current {
+            Some(current) => current + 1,
+            // the current item is gone, go on with the next old item still listed or else the first new one
+            None if removed => following.iter().find_map(|name| self.position(name).ok())
+                .or_else(|| self.list.iter().position(|item| !previous.contains(&item.name)))
+                .unwrap_or(self.list.len()),
+            None => std::cmp::min(self.cursor, self.list.len()),
+        };
+
+        let names: HashSet<&String> = self.list.iter().map(|item| &item.name).collect();
+        self.played.retain(|name| names.contains(name));
+        self.history.retain(|name| names.contains(name));
+    }
+
+    pub fn next_item(&mut self) -> Option<KPAppResourceItem> {
+        self.interrupt = false;
+
+        // continue current directory or playlist file
+        if self.pending.is_none() {
+            let item = match self.entries.as_mut() {
+                Some(entries) => entries.pop_front(),
+                None => self.next_directory_item(),
+            };
+            if let Some(item) = item {
+                self.advance = false;
+                self.playing = Some(item.clone());
+                return Some(item);
+            }
+        }
+        self.directory = None;
+        self.entries = None;
+
+        for _ in 0..=self.list.len() {
+            let index = match self.pending.take() {
+                Some(index) => index,
+                None => self.select_next(),
+            };
+            self.advance = false;
+            let resume = self.resume.take();
+
+            if index >= self.list.len() {
+                self.current = None;
+                self.playing = None;
+                self.cursor = self.list.len();
+                return None;
+            }
+
+            let item = self.list[index].clone();
+            self.current = Some(index);
+            self.cursor = index + 1;
+            self.played.insert(item.name.clone());
+            self.history.push(item.name.clone());
+            if self.history.len() > HISTORY_LIMIT {
+                self.history.remove(0);
+            }
+
+            // expand directory
+            let item = match &item.resource {
+                ResourceItem::Directory { .. } => {
+                    self.directory = Some(HashSet::new());
+                    if let Some(child) = resume {
+                        self.skip_directory_to(&child);
+                    }
+                    match self.next_directory_item() {
+                        Some(sub_item) => sub_item,
+                        None => {
+                            warn!("playlist directory has no playable file. name: {}", item.name);
+                            self.directory = None;
+                            continue;
+                        }
+                    }
+                }
+                ResourceItem::Playlist { .. } => {
+                    let mut entries = self.read_playlist_entries(&item);
+                    if let Some(child) = resume {
+                        let name = format!("{}/{}", item.name, child);
+                        if let Some(position) = entries.iter().position(|entry| entry.name == name) {
+                            entries.drain(..position);
+                        }
+                    }
+                    match entries.pop_front() {
+                        Some(sub_item) => {
+                            self.entries = Some(entries);
+                            sub_item
+                        }
+                        None => {
+                            warn!("playlist file has no playable entry. name: {}", item.name);
+                            continue;
+                        }
+                    }
+                }
+                _ => item,
+            };
+            self.playing = Some(item.clone());
+            return Some(item);
+        }
+
+        warn!("playlist has no playable item");
+        self.current = None;
+        self.playing = None;
+        None
+    }
+
+    // rescan the current directory and pick the first file not yet played in this pass
+    fn next_directory_item(&mut self) -> Option<KPAppResourceItem> {
+        let item = self.list.get(self.current?)?;
+        let directory = match &item.resource {
+            ResourceItem::Directory { directory } => directory,
+            _ => return None,
+        };
+        let played = self.directory.as_mut()?;
+
+        let files = match directory.scan() {
+            Ok(files) => files,
+            Err(err) => {
+                warn!("scan playlist directory failed. path: {}, error: {}", directory.path, err);
+                return None;
+            }
+        };
+        let path = files.into_iter().find(|path| !played.contains(path))?;
+        played.insert(path.clone());
+
+        let relative_path = path.strip_prefix(&directory.path).unwrap_or(&path).to_path_buf();
+        Some(KPAppResourceItem {
+            name: format!("{}/{}", item.name, relative_path.display()),
+            resource: ResourceItem::Single {
+                single: SingleDetail {
+                    path: path.to_string_lossy().to_string(),
+                    expect_streams: directory.expect_streams.clone(),
+                    ..Default::default()
+                },
+            },
+            scene: item.scene.clone(),
+        })
+    }
+
+    // mark the files before the saved one as played, a missing file starts the directory over
+    fn skip_directory_to(&mut self, child: &str) {
+        let directory = match self.current.and_then(|index| self.list.get(index)).map(|item| &item.resource) {
+            Some(ResourceItem::Directory { directory }) => directory,
+            _ => return,
+        };
+        let files = match directory.scan() {
+            Ok(files) => files,
+            Err(_) => return,
+        };
+        let position = match files.iter().position(|path| path.strip_prefix(&directory.path).unwrap_or(path).display().to_string() == child) {
+            Some(position) => position,
+            None => return,
+        };
+        if let Some(played) = self.directory.as_mut() {
+            played.extend(files.into_iter().take(position));
+        }
+    }
+
+    // the file is read again every time the item is reached, edits apply at the next pass
+    fn read_playlist_entries(&self, item: &KPAppResourceItem) -> VecDeque<KPAppResourceItem> {
+        let playlist = match &item.resource {
+            ResourceItem::Playlist { playlist } => playlist,
+            _ => return VecDeque::new(),
+        };
+        let entries = match playlist.read() {
+            Ok(entries) => entries,
+            Err(err) => {
+                warn!("read playlist file failed. path: {}, error: {}", playlist.path, err);
+                return VecDeque::new();
+            }
+        };
+
+        entries.into_iter().enumerate().map(|(index, entry)| {
+            let mut metadata = BTreeMap::new();
+            if let Some(title) = entry.title {
+                metadata.insert("title".to_string(), title);
+            }
+            if let Some(duration) = entry.duration {
+                metadata.insert("duration".to_string(), duration.as_secs_f64().to_string());
+            }
+            KPAppResourceItem {
+                name: format!("{}/{}", item.name, index + 1),
+                resource: ResourceItem::Single {
+                    single: SingleDetail {
+                        path: entry.path,
+                        expect_streams: playlist.expect_streams.clone(),
+                        metadata,
+                        ..Default::default()
+                    },
+                },
+                scene: item.scene.clone(),
+            }
+        }).collect()
+    }
+
+    fn select_next(&mut self) -> usize {
+        let length = self.list.len();
+        if length == 0 {
+            return 0;
+        }
+
+        match self.mode {
+            KPAppResourceMode::Sequence => self.cursor,
+            KPAppResourceMode::RepeatOne if !self.advance && self.current.is_some() => self.current.unwrap(),
+            KPAppResourceMode::RepeatAll | KPAppResourceMode::RepeatOne => {
+                if self.cursor >= length {
+                    self.wrapped = true;
+                    0
+                } else { self.cursor }
+            }
+            KPAppResourceMode::Shuffle => {
+                let mut rng = rand::thread_rng();
+                loop {
+                    let index = rng.gen_range(0..length);
+                    if length == 1 || Some(index) != self.current {
+                        break index;
+                    }
+                }
+            }
+            KPAppResourceMode::ShuffleNoRepeat => {
+                let mut candidates: Vec<usize> = (0..length).filter(|index| !self.played.contains(&self.list[*index].name)).collect();
+                if candidates.is_empty() {
+                    // every item has played, start a new round
+                    self.played.clear();
+                    self.wrapped = true;
+                    candidates = (0..length).filter(|index| length == 1 || Some(*index) != self.current).collect();
+                }
+                *candidates.choose(&mut rand::thread_rng()).unwrap()
+            }
+        }
+    }
+
+    pub fn next(&mut self, immediately: bool) {
+        self.pending = None;
+        self.resume = None;
+        self.advance = true;
+        self.interrupt = immediately;
+    }
+
+    pub fn previous(&mut self, immediately: bool) {
+        if self.list.is_empty() {
+            return;
+        }
+        let index = match self.mode {
+            KPAppResourceMode::Shuffle | KPAppResourceMode::ShuffleNoRepeat => {
+                self.history.pop();
+                self.history.pop().and_then(|name| self.position(name).ok()).or(self.current)
+            }
+            _ => None,
+        };
+        let index = index.unwrap_or_else(|| {
+            let current = self.current.unwrap_or(self.cursor);
+            match (current, &self.mode) {
+                (0, KPAppResourceMode::RepeatAll) => self.list.len().saturating_sub(1),
+                (current, _) => current.saturating_sub(1),
+            }
+        });
+        self.pending = Some(index);
+        self.resume = None;
+        self.interrupt = immediately;
+    }
+
+    pub fn jump<T: ToString>(&mut self, name: T, immediately: bool) -> Result<()> {
+        let index = self.position(name)?;
+        self.pending = Some(index);
+        self.resume = None;
+        self.interrupt = immediately;
+        Ok(())
+    }
+
+    // jump to a saved item, the child names the directory file or playlist entry inside it
+    pub fn resume<T: ToString>(&mut self, name: T, child: Option<String>) -> Result<()> {
+        self.jump(name, false)?;
+        self.resume = child;
+        Ok(())
+    }
+
+    pub fn insert_after(&mut self, name: Option<String>, item: KPAppResourceItem) -> Result<()> {
+        if self.list.iter().any(|exist| exist.name == item.name) {
+            return Err(anyhow!("playlist item name already exists. name: {}", item.name));
+        }
+
+        let index = match name {
+            Some(name) => self.position(name)? + 1,
+            None => self.list.len(),
+        };
+        self.list.insert(index, item);
+
+        // an item inserted right after the current one plays next
+        if index < self.cursor {
+            self.cursor += 1;
+        }
+        if let Some(current) = self.current.as_mut() {
+            if index <= *current { *current += 1; }
+        }
+        if let Some(pending) = self.pending.as_mut() {
+            if index <= *pending { *pending += 1; }
+        }
+        Ok(())
+    }
+
+    pub fn remove<T: ToString>(&mut self, name: T) -> Result<KPAppResourceItem> {
+        let index = self.position(name)?;
+        let item = self.list.remove(index);
+        self.played.remove(&item.name);
+
+        if index < self.cursor {
+            self.cursor -= 1;
+        }
+        self.current = match self.current {
+            Some(current) if current == index => None,
+            Some(current) if current > index => Some(current - 1),
+            other => other,
+        };
+        self.pending = match self.pending {
+            Some(pending) if pending == index => None,
+            Some(pending) if pending > index => Some(pending - 1),
+            other => other,
+        };
+        Ok(item)
+    }
+
+    pub fn take_interrupt(&mut self) -> bool {
+        std::mem::replace(&mut self.interrupt, false)
+    }
+
+    pub fn has_pending(&self) -> bool {
+        self.pending.is_some() || self.advance
+    }
+
+    // true when the next item call would return an item, nothing is consumed
+    pub fn has_playable(&self) -> bool {
+        if self.entries.as_ref().map_or(false, |entries| !entries.is_empty()) {
+            return true;
+        }
+        let candidates: Vec<&KPAppResourceItem> = match (self.pending, &self.mode) {
+            (Some(index), _) => self.list.get(index).into_iter().collect(),
+            (None, KPAppResourceMode::Sequence) => self.list.iter().skip(self.cursor).collect(),
+            _ => self.list.iter().collect(),
+        };
+        candidates.into_iter().any(|item| match &item.resource {
+            ResourceItem::Directory { directory } => directory.scan().map_or(false, |files| !files.is_empty()),
+            ResourceItem::Playlist { playlist } => playlist.read().map_or(false, |entries| !entries.is_empty()),
+            _ => true,
+        })
+    }
+
+    // true once since the list started over from its first item
+    pub fn take_wrapped(&mut self) -> bool {
+        std::mem::replace(&mut self.wrapped, false)
+    }
+
+    pub fn set_mode(&mut self, mode: KPAppResourceMode) {
+        self.mode = mode;
+        self.played.clear();
+    }
+
+    pub fn get_mode(&self) -> &KPAppResourceMode {
+        &self.mode
+    }
+
+    pub fn get_list(&self) -> &Vec<KPAppResourceItem> {
+        &self.list
+    }
+
+    pub fn get_current(&self) -> Option<&KPAppResourceItem> {
+        self.current.and(self.playing.as_ref())
+    }
+
+    // the list item and the directory file or playlist entry being played
+    pub fn get_current_position(&self) -> Option<(String, Option<String>)> {
+        let item = self.list.get(self.current?)?;
+        let playing = self.playing.as_ref()?;
+        let child = match &item.resource {
+            ResourceItem::Directory { .. } | ResourceItem::Playlist { .. } => playing.name.strip_prefix(&format!("{}/", item.name)).map(|child| child.to_string()),
+            _ => None,
+        };
+        Some((item.name.clone(), child))
+    }
+
+    fn position<T: ToString>(&self, name: T) -> Result<usize> {
+        let name = name.to_string();
+        self.list.iter().position(|item| item.name == name).ok_or_else(|| anyhow!("playlist item not found. name: {}", name))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use anyhow::Result;
+    use crate::app::playlist::KPAppPlaylist;
+    use std::collections::HashSet;
+    use std::fs;
+    use crate::util::module::resource::{DirectoryDetail, DirectorySort, KPAppResource, KPAppResourceItem, KPAppResourceMode, PlaylistDetail, ResourceItem, SingleDetail};
+    use crate::util::module::resource::ResourceItem::{Directory, Playlist, Single};
+    use crate::util::common::generate_unique_string;
+
+    fn create_item(name: &str) -> KPAppResourceItem {
+        KPAppResourceItem { name: name.to_string(), resource: Single { single: SingleDetail { path: format!("{}.mp4", name), ..Default::default() } }, scene: None }
+    }
+
+    fn create_playlist(names: Vec<&str>) -> KPAppPlaylist {
+        create_mode_playlist(names, KPAppResourceMode::Sequence)
+    }
+
+    fn create_mode_playlist(names: Vec<&str>, mode: KPAppResourceMode) -> KPAppPlaylist {
+        KPAppPlaylist::new(&KPAppResource { name: "default_playlist".to_string(), mode, list: names.into_iter().map(create_item).collect() })
+    }
+
+    fn next_name(playlist: &mut KPAppPlaylist) -> Option<String> {
+        playlist.next_item().map(|item| item.name)
+    }
+
+    #[test]
+    fn sequence() {
+        let mut playlist = create_playlist(vec!["a", "b", "c"]);
+        assert_eq!(next_name(&mut playlist), Some("a".to_string()));
+        assert_eq!(next_name(&mut playlist), Some("b".to_string()));
+        assert_eq!(next_name(&mut playlist), Some("c".to_string()));
+        assert_eq!(next_name(&mut playlist), None);
+    }
+
+    #[test]
+    fn jump_and_previous() -> Result<()> {
+        let mut playlist = create_playlist(vec!["a", "b", "c", "d"]);
+        assert_eq!(next_name(&mut playlist), Some("a".to_string()));
+
+        playlist.jump("c", true)?;
+        assert!(playlist.take_interrupt());
+        assert!(!playlist.take_interrupt());
+        assert_eq!(next_name(&mut playlist), Some("c".to_string()));
+
+        playlist.previous(false);
+        assert_eq!(next_name(&mut playlist), Some("b".to_string()));
+        assert_eq!(next_name(&mut playlist), Some("c".to_string()));
+
+        assert!(playlist.jump("none", false).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn insert_and_remove() -> Result<()> {
+        let mut playlist = create_playlist(vec!["a", "b", "c"]);
+        assert_eq!(next_name(&mut playlist), Some("a".to_string()));
+
+        playlist.insert_after(Some("a".to_string()), create_item("x"))?;
+        assert!(playlist.insert_after(None, create_item("x")).is_err());
+        playlist.remove("b")?;
+        assert_eq!(playlist.get_current().map(|item| item.name.clone()), Some("a".to_string()));
+
+        assert_eq!(next_name(&mut playlist), Some("x".to_string()));
+        playlist.remove("x")?;
+        assert!(playlist.get_current().is_none());
+        assert_eq!(next_name(&mut playlist), Some("c".to_string()));
+        assert_eq!(next_name(&mut playlist), None);
+        Ok(())
+    }
+
+    #[test]
+    fn remove_pending() -> Result<()> {
+        let mut playlist = create_playlist(vec!["a", "b", "c"]);
+        assert_eq!(next_name(&mut playlist), Some("a".to_string()));
+
+        // the removed last item was pending, a reload must not index past the end
+        playlist.jump("c", false)?;
+        playlist.remove("c")?;
+        assert!(!playlist.has_pending());
+        playlist.replace(&KPAppResource { name: "playlist".to_string(), mode: KPAppResourceMode::Sequence, list: vec![create_item("a"), create_item("b")] });
+        assert_eq!(next_name(&mut playlist), Some("b".to_string()));
+
+        // the pending item is removed, playback goes on from the current one
+        playlist.jump("a", false)?;
+        playlist.remove("a")?;
+        assert_eq!(next_name(&mut playlist), None);
+
+        // nothing to go back to
+        let mut playlist = create_playlist(vec![]);
+        playlist.previous(false);
+        assert!(!playlist.has_pending());
+        playlist.replace(&KPAppResource { name: "playlist".to_string(), mode: KPAppResourceMode::Sequence, list: vec![] });
+        assert_eq!(next_name(&mut playlist), None);
+        Ok(())
+    }
+
+    #[test]
+    fn has_playable() -> Result<()> {
+        let mut playlist = create_playlist(vec!["a"]);
+        assert!(playlist.has_playable());
+        assert_eq!(next_name(&mut playlist), Some("a".to_string()));
+        assert!(!playlist.has_playable());
+        assert_eq!(next_name(&mut playlist), None);
+
+        // checking does not consume the inserted item
+        playlist.insert_after(None, create_item("b"))?;
+        assert!(playlist.has_playable());
+        assert!(playlist.has_playable());
+        assert_eq!(next_name(&mut playlist), Some("b".to_string()));
+
+        playlist.jump("a", false)?;
+        assert!(playlist.has_playable());
+        assert_eq!(next_name(&mut playlist), Some("a".to_string()));
+
+        // an empty directory has nothing to play
+        let dir = std::env::temp_dir().join(generate_unique_string());
+        fs::create_dir_all(&dir)?;
+        let directory = DirectoryDetail {
+            path: dir.to_string_lossy().to_string(),
+            include: vec![],
+            exclude: vec![],
+            recursive: false,
+            sort: DirectorySort::Name,
+            expect_streams: Default::default(),
+        };
+        let mut playlist = create_mode_playlist(vec![], KPAppResourceMode::RepeatAll);
+        playlist.insert_after(None, KPAppResourceItem { name: "dir".to_string(), resource: Directory { directory }, scene: None })?;
+        assert!(!playlist.has_playable());
+        fs::write(dir.join("a.mp4"), "")?;
+        assert!(playlist.has_playable());
+
+        fs::remove_dir_all(dir)?;
+        Ok(())
+    }
+
+    #[test]
+    fn repeat_all() {
+        let mut playlist = create_mode_playlist(vec!["a", "b"], KPAppResourceMode::RepeatAll);
+        assert_eq!(next_name(&mut playlist), Some("a".to_string()));
+        assert_eq!(next_name(&mut playlist), Some("b".to_string()));
+        assert!(!playlist.take_wrapped());
+        assert_eq!(next_name(&mut playlist), Some("a".to_string()));
+        assert!(playlist.take_wrapped());
+        assert!(!playlist.take_wrapped());
+
+        playlist.previous(false);
+        assert_eq!(next_name(&mut playlist), Some("b".to_string()));
+    }
+
+    #[test]
+    fn repeat_one() {
+        let mut playlist = create_mode_playlist(vec!["a", "b"], KPAppResourceMode::RepeatOne);
+        assert_eq!(next_name(&mut playlist), Some("a".to_string()));
+        assert_eq!(next_name(&mut playlist), Some("a".to_string()));
+
+        playlist.next(false);
+        assert!(playlist.has_pending());
+        assert_eq!(next_name(&mut playlist), Some("b".to_string()));
+        assert!(!playlist.has_pending());
+        assert_eq!(next_name(&mut playlist), Some("b".to_string()));
+    }
+
+    #[test]
+    fn shuffle_no_repeat() {
+        let names = vec!["a", "b", "c", "d", "e"];
+        let mut playlist = create_mode_playlist(names.clone(), KPAppResourceMode::ShuffleNoRepeat);
+        for _ in 0..3 {
+            let round: HashSet<String> = (0..names.len()).filter_map(|_| next_name(&mut playlist)).collect();
+            assert_eq!(round.len(), names.len());
+        }
+    }
+
+    #[test]
+    fn shuffle() {
+        let mut playlist = create_mode_playlist(vec!["a", "b", "c"], KPAppResourceMode::Shuffle);
+        let mut latest = next_name(&mut playlist);
+        for _ in 0..20 {
+            let current = next_name(&mut playlist);
+            assert!(current.is_some());
+            assert_ne!(current, latest);
+            latest = current;
+        }
+    }
+
+    #[test]
+    fn directory_rescan() -> Result<()> {
+        let dir = std::env::temp_dir().join(generate_unique_string());
+        fs::create_dir_all(dir.join("sub"))?;
+        for file in ["b.mp4", "a.mkv", "c.txt", "sub/d.mp4"] {
+            fs::write(dir.join(file), "")?;
+        }
+
+        let directory = DirectoryDetail {
+            path: dir.to_string_lossy().to_string(),
+            include: vec![],
+            exclude: vec!["b.*".to_string()],
+            recursive: true,
+            sort: DirectorySort::Name,
+            expect_streams: Default::default(),
+        };
+        let mut playlist = create_mode_playlist(vec!["x"], KPAppResourceMode::RepeatAll);
+        playlist.insert_after(None, KPAppResourceItem { name: "dir".to_string(), resource: Directory { directory }, scene: None })?;
+
+        assert_eq!(next_name(&mut playlist), Some("x".to_string()));
+        assert_eq!(next_name(&mut playlist), Some("dir/a.mkv".to_string()));
+
+        // picked up without restart
+        fs::write(dir.join("e.flv"), "")?;
+        assert_eq!(next_name(&mut playlist), Some("dir/e.flv".to_string()));
+        assert_eq!(next_name(&mut playlist), Some("dir/sub/d.mp4".to_string()));
+        assert_eq!(next_name(&mut playlist), Some("x".to_string()));
+        assert_eq!(next_name(&mut playlist), Some("dir/a.mkv".to_string()));
+
+        fs::remove_dir_all(dir)?;
+        Ok(())
+    }
+
+    #[test]
+    fn playlist_file() -> Result<()> {
+        let dir = std::env::temp_dir().join(generate_unique_string());
+        fs::create_dir_all(&dir)?;
+        fs::write(dir.join("list.m3u8"), "\u{feff}#EXTM3U\n#EXTINF:12.5 tvg-id=\"a\",Title A\nvideo/a.mp4\n\n#EXTINF:-1,Live\nhttp://127.0.0.1/live.flv\n#EXTINF:1e30,Overflow\n/abs/b.mkv\n")?;
+        fs::write(dir.join("list.pls"), "[playlist]\nFile2=b.mp4\nTitle2=Title B\nFile1=a.mp4\nLength1=30\nLength2=1e30\nNumberOfEntries=2\nVersion=2\n")?;
+
+        let playlist_item = |name: &str, file: &str| KPAppResourceItem {
+            name: name.to_string(),
+            resource: Playlist { playlist: PlaylistDetail { path: dir.join(file).to_string_lossy().to_string(), expect_streams: Default::default() } },
+            scene: None,
+        };
+        let single = |item: Option<KPAppResourceItem>| match item.map(|item| item.resource) {
+            Some(ResourceItem::Single { single }) => single,
+            _ => panic!("playlist entry is not a single item"),
+        };
+        let mut playlist = create_mode_playlist(vec!["x"], KPAppResourceMode::RepeatAll);
+        playlist.insert_after(None, playlist_item("m3u", "list.m3u8"))?;
+        playlist.insert_after(None, playlist_item("pls", "list.pls"))?;
+
+        assert_eq!(next_name(&mut playlist), Some("x".to_string()));
+        let a = single(playlist.next_item());
+        assert_eq!(a.path, dir.join("video/a.mp4").to_string_lossy());
+        assert_eq!(a.metadata.get("title").map(|title| title.as_str()), Some("Title A"));
+        assert_eq!(a.metadata.get("duration").map(|duration| duration.as_str()), Some("12.5"));
+        assert_eq!(playlist.get_current().map(|item| item.name.clone()), Some("m3u/1".to_string()));
+
+        let live = single(playlist.next_item());
+        assert_eq!(live.path, "http://127.0.0.1/live.flv");
+        assert!(live.metadata.get("duration").is_none());
+        let overflow = single(playlist.next_item());
+        assert_eq!(overflow.path, "/abs/b.mkv");
+        assert!(overflow.metadata.get("duration").is_none());
+
+        let a = single(playlist.next_item());
+        assert_eq!(a.path, dir.join("a.mp4").to_string_lossy());
+        assert_eq!(a.metadata.get("duration").map(|duration| duration.as_str()), Some("30"));
+        assert_eq!(single(playlist.next_item()).metadata.get("title").map(|title| title.as_str()), Some("Title B"));
+
+        // re-read on the next pass
+        fs::write(dir.join("list.m3u8"), "c.flv\n")?;
+        assert_eq!(next_name(&mut playlist), Some("x".to_string()));
+        assert_eq!(single(playlist.next_item()).path, dir.join("c.flv").to_string_lossy());
+        assert_eq!(next_name(&mut playlist), Some("pls/1".to_string()));
+
+        fs::remove_dir_all(dir)?;
+        Ok(())
+    }
+
+    #[test]
+    fn resume_expanded() -> Result<()> {
+        let dir = std::env::temp_dir().join(generate_unique_string());
+        fs::create_dir_all(&dir)?;
+        for file in ["a.mp4", "b.mp4", "c.mp4"] {
+            fs::write(dir.join(file), "")?;
+        }
+        fs::write(dir.join("list.m3u8"), "a.mp4\nb.mp4\nc.mp4\n")?;
+
+        let directory = DirectoryDetail {
+            path: dir.to_string_lossy().to_string(),
+            include: vec!["*.mp4".to_string()],
+            exclude: vec![],
+            recursive: false,
+            sort: DirectorySort::Name,
+            expect_streams: Default::default(),
+        };
+        let mut playlist = create_playlist(vec!["x"]);
+        playlist.insert_after(None, KPAppResourceItem { name: "dir".to_string(), resource: Directory { directory }, scene: None })?;
+        playlist.insert_after(None, KPAppResourceItem {
+            name: "m3u".to_string(),
+            resource: Playlist { playlist: PlaylistDetail { path: dir.join("list.m3u8").to_string_lossy().to_string(), expect_streams: Default::default() } },
+            scene: None,
+        })?;
+
+        // the saved name of an expanded item is not a list item
+        assert!(playlist.jump("dir/b.mp4", false).is_err());
+
+        playlist.resume("dir", Some("b.mp4".to_string()))?;
+        assert_eq!(next_name(&mut playlist), Some("dir/b.mp4".to_string()));
+        assert_eq!(playlist.get_current_position(), Some(("dir".to_string(), Some("b.mp4".to_string()))));
+        assert_eq!(next_name(&mut playlist), Some("dir/c.mp4".to_string()));
+
+        playlist.resume("m3u", Some("2".to_string()))?;
+        assert_eq!(next_name(&mut playlist), Some("m3u/2".to_string()));
+        assert_eq!(next_name(&mut playlist), Some("m3u/3".to_string()));
+        assert_eq!(next_name(&mut playlist), None);
+
+        // a missing child starts the item over
+        playlist.resume("dir", Some("gone.mp4".to_string()))?;
+        assert_eq!(next_name(&mut playlist), Some("dir/a.mp4".to_string()));
+
+        playlist.resume("x", None)?;
+        assert_eq!(next_name(&mut playlist), Some("x".to_string()));
+        assert_eq!(playlist.get_current_position(), Some(("x".to_string(), None)));
+
+        // a bare string item is found again after a restart
+        let mut playlist = KPAppPlaylist::new(&serde_json::from_str(r#"{"name": "playlist", "list": ["a.mp4", "b.mp4"]}"#)?);
+        playlist.resume("b.mp4", None)?;
+        assert_eq!(next_name(&mut playlist), Some("b.mp4".to_string()));
+
+        fs::remove_dir_all(dir)?;
+        Ok(())
+    }
+
+    #[test]
+    fn replace_list() {
+        let mut playlist = create_playlist(vec!["a", "b", "c"]);
+        assert_eq!(next_name(&mut playlist), Some("a".to_string()));
+        assert_eq!(next_name(&mut playlist), Some("b".to_string()));
+
+        // current item b moves, playback continues after it
+        playlist.replace(&KPAppResource { name: "playlist".to_string(), mode: KPAppResourceMode::Sequence, list: vec![create_item("d"), create_item("b"), create_item("e")] });
+        assert_eq!(playlist.get_current().unwrap().name, "b");
+        assert_eq!(next_name(&mut playlist), Some("e".to_string()));
+
+        // current item removed, the new items play
+        playlist.replace(&KPAppResource { name: "playlist".to_string(), mode: KPAppResourceMode::Sequence, list: vec![create_item("f"), create_item("g")] });
+        assert_eq!(next_name(&mut playlist), Some("f".to_string()));
+
+        // current item removed, the next old item goes on
+        playlist.replace(&KPAppResource { name: "playlist".to_string(), mode: KPAppResourceMode::Sequence, list: vec![create_item("h"), create_item("g")] });
+        assert_eq!(next_name(&mut playlist), Some("g".to_string()));
+
+        // the last item removed, nothing is replayed
+        playlist.replace(&KPAppResource { name: "playlist".to_string(), mode: KPAppResourceMode::Sequence, list: vec![create_item("h")] });
+        assert_eq!(next_name(&mut playlist), None);
+    }
+
+    #[test]
+    fn replace_parsed_list() -> Result<()> {
+        let resource: KPAppResource = serde_json::from_str(r#"{"name": "playlist", "list": ["a.mp4", "b.mp4", "a.mp4"]}"#)?;
+        let names: Vec<&str> = resource.list.iter().map(|item| item.name.as_str()).collect();
+        assert_eq!(names, vec!["a.mp4", "b.mp4", "a.mp4#3"]);
+
+        // the same config parsed again keeps the current item
+        let mut playlist = KPAppPlaylist::new(&resource);
+        assert_eq!(next_name(&mut playlist), Some("a.mp4".to_string()));
+        assert_eq!(next_name(&mut playlist), Some("b.mp4".to_string()));
+        playlist.replace(&serde_json::from_str(r#"{"name": "playlist", "list": ["a.mp4", "b.mp4", "a.mp4", "c.mp4"]}"#)?);
+        assert_eq!(playlist.get_current().unwrap().name, "b.mp4");
+        assert_eq!(next_name(&mut playlist), Some("a.mp4#3".to_string()));
+        assert_eq!(next_name(&mut playlist), Some("c.mp4".to_string()));
+        Ok(())
+    }
+}
@@ -0,0 +1,181 @@
+use std::fs;
+use std::path::PathBuf;
+use std::thread::JoinHandle;
+use std::time::{Duration, SystemTime};
+use anyhow::{anyhow, Result};
+use log::{debug, info, warn};
+use serde::Serialize;
+use crate::app::controller::KPAppController;
+use crate::util::config::KPAppConfig;
+use crate::util::module::resource::KPAppResource;
+use crate::util::module::scene::KPAppScene;
+
+const RELOAD_CHECK_INTERVAL: Duration = Duration::from_secs(1);
+
+#[derive(Debug, Default)]
+pub struct KPAppReload {
+    pub playlist: Option<KPAppResource>,
+    pub scene: Option<KPAppScene>,
+}
+
+impl KPAppReload {
+    pub fn is_empty(&self) -> bool {
+        self.playlist.is_none() && self.scene.is_none()
+    }
+}
+
+pub struct KPAppReloader {
+    config_path: PathBuf,
+    config: KPAppConfig,
+    modified: Option<SystemTime>,
+}
+
+impl KPAppReloader {
+    pub fn new(config_path: PathBuf, config: KPAppConfig) -> Self {
+        let modified = fs::metadata(&config_path).and_then(|m| m.modified()).ok();
+        KPAppReloader {
+            config_path,
+            config,
+            modified,
+        }
+    }
+
+    // sections that can not change without restarting the process
+    fn restart_sections(current: &KPAppConfig, config: &KPAppConfig) -> Result<Vec<&'static str>> {
+        let mut sections = Vec::new();
+        if !Self::section_eq(&current.output, &config.output)? { sections.push("output"); }
+        if !Self::section_eq(&current.encode, &config.encode)? { sections.push("encode"); }
+        if !Self::section_eq(&current.schedule, &config.schedule)? { sections.push("schedule"); }
+        if !Self::section_eq(&current.failure, &config.failure)? { sections.push("failure"); }
+        if !Self::section_eq(&current.server, &config.server)? { sections.push("server"); }
+        if !Self::section_eq(&current.api, &config.api)? { sections.push("api"); }
+        if !Self::section_eq(&current.metrics, &config.metrics)? { sections.push("metrics"); }
+        if !Self::section_eq(&current.shutdown, &config.shutdown)? { sections.push("shutdown"); }
+        if !Self::section_eq(&current.fallback, &config.fallback)? { sections.push("fallback"); }
+        Ok(sections)
+    }
+
+    fn section_eq<T: Serialize>(current: &T, config: &T) -> Result<bool> {
+        Ok(serde_json::to_value(current)? == serde_json::to_value(config)?)
+    }
+
+    pub fn diff(current: &KPAppConfig, config: &KPAppConfig) -> Result<KPAppReload> {
+        let sections = Self::restart_sections(current, config)?;
+        if !sections.is_empty() {
+            return Err(anyhow!("config change requires restart, reload rejected. sections: {}", sections.join(", ")));
+        }
+
+        let mut reload = KPAppReload::default();
+        if !Self::section_eq(&current.playlist, &config.playlist)? {
+            // the loaded list belongs to a schedule entry or the fallback, replacing it would drop the scheduled program
+            match config.schedule.is_some() {
+                true => warn!("playlist change is not applied while a schedule is configured, restart to apply"),
+                false => reload.playlist = Some(config.playlist.clone()),
+            }
+        }
+        if !Self::section_eq(&current.scene, &config.scene)? {
+            reload.scene = Some(config.scene.clone());
+        }
+        Ok(reload)
+    }
+
+    pub fn check(&mut self) -> Result<Option<KPAppReload>> {
+        let modified = fs::metadata(&self.config_path).and_then(|m| m.modified()).ok();
+        if modified == self.modified {
+            return Ok(None);
+        }
+        self.modified = modified;
+        debug!("config file modified. path: {}", self.config_path.display());
+
+        let config = KPAppConfig::from_file(&self.config_path).map_err(|err| anyhow!("load config file failed, reload rejected. error: {}", err))?;
+        let reload = Self::diff(&self.config, &config)?;
+        self.config = config;
+        Ok(Some(reload))
+    }
+
+    pub fn watch(mut self, controller: KPAppController) -> JoinHandle<()> {
+        info!("watch config file. path: {}", self.config_path.display());
+        std::thread::spawn(move || loop {
+            std::thread::sleep(RELOAD_CHECK_INTERVAL);
+            let reload = match self.check() {
+                Ok(Some(reload)) => reload,
+                Ok(None) => continue,
+                Err(err) => {
+                    warn!("reload config failed. error: {}", err);
+                    continue;
+                }
+            };
+            if reload.is_empty() {
+                continue;
+            }
+            if let Some(playlist) = reload.playlist {
+                if let Err(err) = controller.replace(&playlist) {
+                    warn!("reload playlist failed. error: {}", err);
+                }
+            }
+            if let Some(scene) = reload.scene {
+                if let Err(err) = controller.update_scene(scene) {
+                    warn!("reload scene failed. error: {}", err);
+                }
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use anyhow::Result;
+    use crate::app::reload::KPAppReloader;
+    use crate::util::config::KPAppConfig;
+    use crate::util::module::encode::KPAppEncode;
+    use crate::util::module::failure::KPAppFailure;
+    use crate::util::module::server::KPAppServer;
+    use crate::util::module::shutdown::KPAppShutdown;
+    use crate::util::module::output::KPAppOutput;
+    use crate::util::module::resource::{KPAppResource, KPAppResourceMode};
+    use crate::util::module::scene::{KPAppPlugin, KPAppScene};
+    use crate::util::module::schedule::KPAppSchedule;
+
+    fn create_config() -> KPAppConfig {
+        KPAppConfig {
+            playlist: KPAppResource { name: "playlist".to_string(), mode: KPAppResourceMode::Sequence, list: vec![] },
+            output: vec![KPAppOutput::new("push", "rtmp://127.0.0.1:1935/live/test")],
+            scene: KPAppScene { name: "scene".to_string(), list: vec![KPAppPlugin { name: "text".to_string(), arguments: Default::default() }] },
+            schedule: None,
+            encode: KPAppEncode::default(),
+            failure: KPAppFailure::default(),
+            server: KPAppServer::default(),
+            api: None,
+            metrics: None,
+            shutdown: KPAppShutdown::default(),
+            fallback: None,
+        }
+    }
+
+    #[test]
+    fn diff_sections() -> Result<()> {
+        let current = create_config();
+        assert!(KPAppReloader::diff(&current, &create_config())?.is_empty());
+
+        let mut config = create_config();
+        config.playlist.mode = KPAppResourceMode::RepeatAll;
+        config.scene.list[0].arguments.insert("text".to_string(), "changed".to_string());
+        let reload = KPAppReloader::diff(&current, &config)?;
+        assert!(reload.playlist.is_some());
+        assert!(reload.scene.is_some());
+
+        let mut config = create_config();
+        config.output.push(KPAppOutput::new("backup", "rtmp://127.0.0.1:1935/live/backup"));
+        config.encode.video.width = 1280;
+        let err = KPAppReloader::diff(&current, &config).unwrap_err();
+        assert!(err.to_string().contains("output, encode"));
+
+        // the playlist is not swapped under a schedule
+        let mut current = create_config();
+        current.schedule = Some(KPAppSchedule { list: vec![], fallback: None });
+        let mut config = current.clone();
+        config.playlist.mode = KPAppResourceMode::RepeatAll;
+        assert!(KPAppReloader::diff(&current, &config)?.is_empty());
+        Ok(())
+    }
+}
@@ -0,0 +1,266 @@
+use std::str::FromStr;
+use std::sync::Arc;
+use anyhow::{anyhow, Result};
+use chrono::{DateTime, Local, NaiveTime, TimeZone};
+use crate::util::module::resource::KPAppResource;
+use crate::util::module::schedule::{KPAppSchedule, KPAppScheduleItem, KPAppSchedulePolicy};
+use crate::util::module::validator::schedule::parse_schedule_time;
+
+pub trait KPAppClock: Send + Sync {
+    fn now(&self) -> DateTime<Local>;
+}
+
+pub struct KPAppSystemClock;
+
+impl KPAppClock for KPAppSystemClock {
+    fn now(&self) -> DateTime<Local> {
+        Local::now()
+    }
+}
+
+enum KPAppScheduleTrigger {
+    Daily(NaiveTime),
+    Cron(cron::Schedule),
+}
+
+impl KPAppScheduleTrigger {
+    fn new(item: &KPAppScheduleItem) -> Result<Self> {
+        if let Some(start) = &item.start {
+            let time = parse_schedule_time(start).ok_or_else(|| anyhow!("invalid schedule start time. name: {}, start: {}", item.name, start))?;
+            return Ok(KPAppScheduleTrigger::Daily(time));
+        }
+        if let Some(expression) = &item.cron {
+            let schedule = cron::Schedule::from_str(expression).map_err(|err| anyhow!("invalid schedule cron expression. name: {}, cron: {}, error: {}", item.name, expression, err))?;
+            return Ok(KPAppScheduleTrigger::Cron(schedule));
+        }
+        Err(anyhow!("schedule entry has no start time or cron expression. name: {}", item.name))
+    }
+
+    // latest fire time within (from, to]
+    fn latest(&self, from: &DateTime<Local>, to: &DateTime<Local>) -> Option<DateTime<Local>> {
+        match self {
+            KPAppScheduleTrigger::Daily(time) => {
+                let today = to.date_naive();
+                [Some(today), today.pred_opt()].into_iter().flatten()
+                    .filter_map(|date| Local.from_local_datetime(&date.and_time(*time)).earliest())
+                    .find(|fire| fire <= to)
+                    .filter(|fire| fire > from)
+            }
+            KPAppScheduleTrigger::Cron(schedule) => schedule.after(from).take_while(|fire| fire <= to).last(),
+        }
+    }
+
+    // latest fire time at or before to
+    fn previous(&self, to: &DateTime<Local>) -> Option<DateTime<Local>> {
+        match self {
+            KPAppScheduleTrigger::Daily(_) => self.latest(&(*to - chrono::Duration::days(1)), to),
+            KPAppScheduleTrigger::Cron(schedule) => schedule.after(&(*to + chrono::Duration::seconds(1))).next_back().filter(|fire| fire <= to),
+        }
+    }
+}
+
+pub struct KPAppScheduler {
+    entries: Vec<(KPAppScheduleItem, KPAppScheduleTrigger)>,
+    fallback: KPAppResource,
+    clock: Arc<dyn KPAppClock>,
+
+    // state
+    last_check: DateTime<Local>,
+    pending: Option<usize>,
+}
+
+impl KPAppScheduler {
+    pub fn new(schedule: &KPAppSchedule, fallback: KPAppResource, clock: Arc<dyn KPAppClock>) -> Result<Self> {
+        let mut entries = Vec::new();
+        for item in schedule.list.iter() {
+            entries.push((item.clone(), KPAppScheduleTrigger::new(item)?));
+        }
+        let mut scheduler = KPAppScheduler {
+            entries,
+            fallback,
+            last_check: clock.now(),
+            clock,
+            pending: None,
+        };
+        scheduler.pending = scheduler.current();
+        Ok(scheduler)
+    }
+
+    pub fn set_clock(&mut self, clock: Arc<dyn KPAppClock>) {
+        self.last_check = clock.now();
+        self.clock = clock;
+        self.pending = self.current();
+    }
+
+    // the entry whose slot is in progress, it started most recently
+    fn current(&self) -> Option<usize> {
+        let mut latest: Option<(usize, DateTime<Local>)> = None;
+        for (index, (_, trigger)) in self.entries.iter().enumerate() {
+            if let Some(fire) = trigger.previous(&self.last_check) {
+                if latest.map_or(true, |(_, exist)| fire >= exist) {
+                    latest = Some((index, fire));
+                }
+            }
+        }
+        latest.map(|(index, _)| index)
+    }
+
+    // checks the entries fired since the last poll, the most recent one wins
+    pub fn poll(&mut self) {
+        let now = self.clock.now();
+        if now < self.last_check {
+            // clock moved backwards, restart the window
+            self.last_check = now;
+            return;
+        }
+
+        let mut latest: Option<(usize, DateTime<Local>)> = None;
+        for (index, (_, trigger)) in self.entries.iter().enumerate() {
+            if let Some(fire) = trigger.latest(&self.last_check, &now) {
+                if latest.map_or(true, |(_, exist)| fire >= exist) {
+                    latest = Some((index, fire));
+                }
+            }
+        }
+        if let Some((index, _)) = latest {
+            self.pending = Some(index);
+        }
+        self.last_check = now;
+    }
+
+    pub fn has_pending(&self) -> bool {
+        self.pending.is_some()
+    }
+
+    pub fn is_hard_cut(&self) -> bool {
+        match self.pending {
+            Some(index) => self.entries[index].0.policy == KPAppSchedulePolicy::HardCut,
+            None => false,
+        }
+    }
+
+    pub fn take_pending(&mut self) -> Option<KPAppScheduleItem> {
+        self.pending.take().map(|index| self.entries[index].0.clone())
+    }
+
+    pub fn get_fallback(&self) -> &KPAppResource {
+        &self.fallback
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+    use anyhow::Result;
+    use chrono::{DateTime, Duration, Local, TimeZone};
+    use crate::app::schedule::{KPAppClock, KPAppScheduler};
+    use crate::util::module::resource::{KPAppResource, KPAppResourceItem, KPAppResourceMode, ResourceItem, SingleDetail};
+    use crate::util::module::schedule::{KPAppSchedule, KPAppScheduleItem, KPAppSchedulePolicy};
+
+    struct ManualClock {
+        now: Mutex<DateTime<Local>>,
+    }
+
+    impl ManualClock {
+        fn new(hour: u32, minute: u32, second: u32) -> Arc<Self> {
+            Arc::new(ManualClock { now: Mutex::new(Local.with_ymd_and_hms(2024, 1, 1, hour, minute, second).unwrap()) })
+        }
+
+        fn advance(&self, duration: Duration) {
+            let mut now = self.now.lock().unwrap();
+            *now = *now + duration;
+        }
+    }
+
+    impl KPAppClock for ManualClock {
+        fn now(&self) -> DateTime<Local> {
+            *self.now.lock().unwrap()
+        }
+    }
+
+    fn create_entry(name: &str, start: Option<&str>, cron: Option<&str>, policy: KPAppSchedulePolicy) -> KPAppScheduleItem {
+        KPAppScheduleItem {
+            name: name.to_string(),
+            start: start.map(|s| s.to_string()),
+            cron: cron.map(|s| s.to_string()),
+            policy,
+            mode: KPAppResourceMode::Sequence,
+            list: vec![KPAppResourceItem { name: format!("{}_item", name), resource: ResourceItem::Single { single: SingleDetail { path: format!("{}.mp4", name), ..Default::default() } }, scene: None }],
+        }
+    }
+
+    fn create_scheduler(clock: Arc<ManualClock>) -> Result<KPAppScheduler> {
+        let schedule = KPAppSchedule {
+            list: vec![
+                create_entry("news", Some("08:00"), None, KPAppSchedulePolicy::HardCut),
+                create_entry("film", Some("20:00:00"), None, KPAppSchedulePolicy::Wait),
+                create_entry("hourly", None, Some("0 30 * * * *"), KPAppSchedulePolicy::Wait),
+            ],
+            fallback: None,
+        };
+        let fallback = KPAppResource { name: "fallback".to_string(), mode: KPAppResourceMode::RepeatAll, list: vec![] };
+        KPAppScheduler::new(&schedule, fallback, clock)
+    }
+
+    #[test]
+    fn daily_start() -> Result<()> {
+        let clock = ManualClock::new(7, 59, 0);
+        let mut scheduler = create_scheduler(clock.clone())?;
+
+        // starts with the slot in progress
+        assert_eq!(scheduler.take_pending().unwrap().name, "hourly");
+        scheduler.poll();
+        assert!(!scheduler.has_pending());
+
+        clock.advance(Duration::seconds(59));
+        scheduler.poll();
+        assert!(!scheduler.has_pending());
+
+        clock.advance(Duration::seconds(1));
+        scheduler.poll();
+        assert!(scheduler.is_hard_cut());
+        assert_eq!(scheduler.take_pending().unwrap().name, "news");
+
+        // fires once per day
+        clock.advance(Duration::seconds(1));
+        scheduler.poll();
+        assert!(!scheduler.has_pending());
+        Ok(())
+    }
+
+    #[test]
+    fn cron_and_wait_policy() -> Result<()> {
+        let clock = ManualClock::new(19, 29, 59);
+        let mut scheduler = create_scheduler(clock.clone())?;
+        assert_eq!(scheduler.take_pending().unwrap().name, "hourly");
+
+        clock.advance(Duration::seconds(1));
+        scheduler.poll();
+        assert!(scheduler.has_pending());
+        assert!(!scheduler.is_hard_cut());
+        assert_eq!(scheduler.take_pending().unwrap().name, "hourly");
+
+        // the most recent entry wins after a long gap between polls
+        clock.advance(Duration::minutes(31));
+        scheduler.poll();
+        assert_eq!(scheduler.take_pending().unwrap().name, "film");
+        Ok(())
+    }
+
+    #[test]
+    fn start_in_slot() -> Result<()> {
+        // the daily entry fired ten minutes ago is still in progress
+        let clock = ManualClock::new(20, 10, 0);
+        let mut scheduler = create_scheduler(clock.clone())?;
+        assert_eq!(scheduler.take_pending().unwrap().name, "film");
+
+        // a fire time exactly at the start counts
+        let clock = ManualClock::new(8, 0, 0);
+        let mut scheduler = create_scheduler(clock.clone())?;
+        assert_eq!(scheduler.take_pending().unwrap().name, "news");
+
+        scheduler.set_clock(ManualClock::new(20, 40, 0));
+        assert_eq!(scheduler.take_pending().unwrap().name, "hourly");
+        Ok(())
+    }
+}
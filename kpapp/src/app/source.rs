@@ -0,0 +1,84 @@
+use std::collections::HashMap;
+use std::time::Duration;
+use anyhow::Result;
+use kpcodec::decode::decode::KPDecode;
+use kpcodec::decode::mix::KPMixCodec;
+use kpcodec::filter::graph_source::{KPGraphSourceAttribute, KPGraphSourceRely};
+use kpcodec::util::alias::{KPAVFrame, KPAVMediaType};
+use kpcodec::util::codec_status::KPCodecStatus;
+
+pub enum KPAppSource {
+    Single(KPDecode),
+    Mix(KPMixCodec),
+}
+
+impl KPGraphSourceRely for KPAppSource {
+    fn get_source(&self, media_type: &KPAVMediaType) -> Result<KPGraphSourceAttribute> {
+        match self {
+            KPAppSource::Single(decode) => decode.get_source(media_type),
+            KPAppSource::Mix(mix) => mix.get_source(media_type),
+        }
+    }
+}
+
+impl KPAppSource {
+    pub fn set_enable_loop(&mut self, enable: bool) {
+        match self {
+            KPAppSource::Single(decode) => decode.set_enable_loop(enable),
+            KPAppSource::Mix(mix) => mix.set_enable_loop(enable),
+        }
+    }
+
+    pub fn open(&mut self) -> Result<()> {
+        match self {
+            KPAppSource::Single(decode) => {
+                decode.open()?;
+                decode.find_streams()?;
+                decode.open_codec()?;
+                decode.stream_to_codec()?;
+            }
+            KPAppSource::Mix(mix) => {
+                mix.open()?;
+                mix.find_streams()?;
+                mix.open_codec()?;
+            }
+        }
+        Ok(())
+    }
+
+    pub fn next_frame(&mut self) -> Option<Result<(KPAVMediaType, KPAVFrame)>> {
+        match self {
+            KPAppSource::Single(decode) => decode.iter().next(),
+            KPAppSource::Mix(mix) => mix.iter().next(),
+        }
+    }
+
+    pub fn get_status(&self) -> &KPCodecStatus {
+        match self {
+            KPAppSource::Single(decode) => decode.get_status(),
+            KPAppSource::Mix(mix) => mix.get_status(),
+        }
+    }
+
+    pub fn get_expect_streams(&self) -> HashMap<KPAVMediaType, Option<usize>> {
+        match self {
+            KPAppSource::Single(decode) => decode.get_expect_streams().clone(),
+            KPAppSource::Mix(mix) => mix.get_expect_streams(),
+        }
+    }
+
+    pub fn get_loop_count(&self) -> usize {
+        match self {
+            KPAppSource::Single(decode) => decode.get_loop_count(),
+            KPAppSource::Mix(mix) => mix.get_loop_count(),
+        }
+    }
+
+    // mixed sources can not be resumed, they have no single timeline
+    pub fn get_media_position(&self) -> Option<Duration> {
+        match self {
+            KPAppSource::Single(decode) => Some(decode.get_media_position()),
+            KPAppSource::Mix(_) => None,
+        }
+    }
+}
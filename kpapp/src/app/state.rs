@@ -0,0 +1,94 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+use crate::util::module::deserialize::duration::{duration, serialize_duration};
+
+pub const STATE_FILENAME: &str = "kplayer.state.json";
+
+#[derive(Serialize, Deserialize, Clone, Debug, Eq, PartialEq)]
+pub struct KPAppState {
+    pub item: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub child: Option<String>,
+    #[serde(deserialize_with = "duration", serialize_with = "serialize_duration")]
+    pub position: Duration,
+}
+
+impl KPAppState {
+    pub fn new<T: ToString>(item: T, child: Option<String>, position: Duration) -> Self {
+        KPAppState {
+            item: item.to_string(),
+            child,
+            position,
+        }
+    }
+
+    // the name of the played item, a directory file or playlist entry is named after its list item
+    pub fn get_name(&self) -> String {
+        match &self.child {
+            Some(child) => format!("{}/{}", self.item, child),
+            None => self.item.clone(),
+        }
+    }
+
+    pub fn get_path(home_dir: &Path) -> PathBuf {
+        home_dir.join(STATE_FILENAME)
+    }
+
+    pub fn load(path: &Path) -> Result<Option<Self>> {
+        if !path.exists() {
+            return Ok(None);
+        }
+        let content = fs::read_to_string(path).map_err(|err| anyhow!("read state file failed. path: {}, error: {}", path.display(), err))?;
+        let state = serde_json::from_str(&content).map_err(|err| anyhow!("parse state file failed. path: {}, error: {}", path.display(), err))?;
+        Ok(Some(state))
+    }
+
+    // write to a temporary file first so a crash never leaves a truncated state
+    pub fn save(&self, path: &Path) -> Result<()> {
+        let temporary_path = path.with_extension("tmp");
+        let content = serde_json::to_string(self)?;
+        fs::write(&temporary_path, content).map_err(|err| anyhow!("write state file failed. path: {}, error: {}", temporary_path.display(), err))?;
+        fs::rename(&temporary_path, path).map_err(|err| anyhow!("rename state file failed. path: {}, error: {}", path.display(), err))?;
+        Ok(())
+    }
+
+    pub fn remove(path: &Path) -> Result<()> {
+        if path.exists() {
+            fs::remove_file(path).map_err(|err| anyhow!("remove state file failed. path: {}, error: {}", path.display(), err))?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::env;
+    use std::time::Duration;
+    use anyhow::Result;
+    use crate::app::state::KPAppState;
+    use crate::util::common::generate_unique_string;
+
+    #[test]
+    fn save_and_load() -> Result<()> {
+        let home_dir = env::temp_dir().join(generate_unique_string());
+        std::fs::create_dir_all(&home_dir)?;
+        let path = KPAppState::get_path(&home_dir);
+        assert!(KPAppState::load(&path)?.is_none());
+
+        let state = KPAppState::new("movie", None, Duration::from_millis(12500));
+        state.save(&path)?;
+        assert_eq!(KPAppState::load(&path)?, Some(state));
+
+        let state = KPAppState::new("dir", Some("sub/a.mp4".to_string()), Duration::from_secs(3));
+        state.save(&path)?;
+        assert_eq!(KPAppState::load(&path)?.map(|state| state.get_name()), Some("dir/sub/a.mp4".to_string()));
+
+        KPAppState::remove(&path)?;
+        assert!(KPAppState::load(&path)?.is_none());
+        std::fs::remove_dir_all(&home_dir)?;
+        Ok(())
+    }
+}
@@ -1,11 +1,35 @@
 use std::borrow::Cow;
 use std::env;
 use std::ffi::OsStr;
-use clap::{arg, command, crate_version, Arg, Command};
+use clap::{arg, command, crate_version, Arg, ArgAction, Command};
 
 pub const HOMEDIR_FLAG: &str = "home";
 pub const CONFIG_PATH_FLAG: &str = "config";
 pub const LOGLEVEL_FLAG: &str = "log_level";
+pub const IGNORE_STATE_FLAG: &str = "ignore_state";
+pub const JSON_FLAG: &str = "json";
+pub const FILE_ARG: &str = "file";
+
+pub const VALIDATE_COMMAND: &str = "validate";
+pub const PROBE_COMMAND: &str = "probe";
+pub const PLUGIN_COMMAND: &str = "plugin";
+pub const PLUGIN_INFO_COMMAND: &str = "info";
+pub const CONFIG_COMMAND: &str = "config";
+pub const CONFIG_SCHEMA_COMMAND: &str = "schema";
+
+fn json_arg() -> Arg {
+    Arg::new(JSON_FLAG)
+        .long("json")
+        .action(ArgAction::SetTrue)
+        .help("Print the output as JSON")
+}
+
+fn file_arg(help: &'static str) -> Arg {
+    Arg::new(FILE_ARG)
+        .value_name("FILE")
+        .required(true)
+        .help(help)
+}
 
 pub fn cli() -> Command {
     Command::new("kplayer")
@@ -32,19 +56,92 @@ pub fn cli() -> Command {
                 .value_parser(["trace", "debug", "info", "warn", "error"])
                 .help("Set the level of logging"),
         )
+        .arg(
+            Arg::new(IGNORE_STATE_FLAG)
+                .long("ignore-state")
+                .action(ArgAction::SetTrue)
+                .help("Ignore the saved playback state and start from the first item"),
+        )
+        .subcommand(
+            Command::new(VALIDATE_COMMAND)
+                .about("Validate the configuration and load every plugin without streaming")
+                .arg(json_arg()),
+        )
+        .subcommand(
+            Command::new(PROBE_COMMAND)
+                .about("Print the streams, duration, codecs and metadata of a media file")
+                .arg(file_arg("The media file or url to probe"))
+                .arg(json_arg()),
+        )
+        .subcommand(
+            Command::new(PLUGIN_COMMAND)
+                .about("Inspect plugins")
+                .subcommand_required(true)
+                .subcommand(
+                    Command::new(PLUGIN_INFO_COMMAND)
+                        .about("Print the app, author, version, media type, groups and arguments of a plugin")
+                        .arg(file_arg("The plugin file (.kpe)"))
+                        .arg(json_arg()),
+                ),
+        )
+        .subcommand(
+            Command::new(CONFIG_COMMAND)
+                .about("Inspect the configuration format")
+                .subcommand_required(true)
+                .subcommand(
+                    Command::new(CONFIG_SCHEMA_COMMAND)
+                        .about("Print the JSON Schema of kplayer.json, scene entries are checked against the installed plugins"),
+                ),
+        )
 }
 
 #[cfg(test)]
 mod tests {
     use anyhow::Result;
     use log::info;
-    use crate::cmd::cli::cli;
+    use crate::cmd::cli::{cli, CONFIG_COMMAND, CONFIG_SCHEMA_COMMAND, FILE_ARG, IGNORE_STATE_FLAG, JSON_FLAG, PLUGIN_COMMAND, PLUGIN_INFO_COMMAND, PROBE_COMMAND, VALIDATE_COMMAND};
 
     #[test]
     fn test_cli() -> Result<()> {
         let cmd = cli();
         let matches = cmd.get_matches_from(vec!["kplayer"]);
         info!("{:?}", matches);
+        assert!(!matches.get_flag(IGNORE_STATE_FLAG));
+
+        let matches = cli().get_matches_from(vec!["kplayer", "--ignore-state"]);
+        assert!(matches.get_flag(IGNORE_STATE_FLAG));
+        Ok(())
+    }
+
+    #[test]
+    fn test_subcommand() -> Result<()> {
+        let matches = cli().get_matches_from(vec!["kplayer", "validate", "--json"]);
+        let (name, sub_matches) = matches.subcommand().unwrap();
+        assert_eq!(name, VALIDATE_COMMAND);
+        assert!(sub_matches.get_flag(JSON_FLAG));
+
+        let matches = cli().get_matches_from(vec!["kplayer", "probe", "video.mp4"]);
+        let (name, sub_matches) = matches.subcommand().unwrap();
+        assert_eq!(name, PROBE_COMMAND);
+        assert_eq!(sub_matches.get_one::<String>(FILE_ARG).unwrap(), "video.mp4");
+        assert!(!sub_matches.get_flag(JSON_FLAG));
+
+        let matches = cli().get_matches_from(vec!["kplayer", "plugin", "info", "text.kpe", "--json"]);
+        let (name, sub_matches) = matches.subcommand().unwrap();
+        assert_eq!(name, PLUGIN_COMMAND);
+        let (name, info_matches) = sub_matches.subcommand().unwrap();
+        assert_eq!(name, PLUGIN_INFO_COMMAND);
+        assert_eq!(info_matches.get_one::<String>(FILE_ARG).unwrap(), "text.kpe");
+        assert!(info_matches.get_flag(JSON_FLAG));
+
+        let matches = cli().get_matches_from(vec!["kplayer", "config", "schema"]);
+        let (name, sub_matches) = matches.subcommand().unwrap();
+        assert_eq!(name, CONFIG_COMMAND);
+        assert_eq!(sub_matches.subcommand_name(), Some(CONFIG_SCHEMA_COMMAND));
+
+        assert!(cli().try_get_matches_from(vec!["kplayer", "probe"]).is_err());
+        assert!(cli().try_get_matches_from(vec!["kplayer", "plugin"]).is_err());
+        assert!(cli().try_get_matches_from(vec!["kplayer", "config"]).is_err());
         Ok(())
     }
 }
\ No newline at end of file
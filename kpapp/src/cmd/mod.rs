@@ -1 +1,17 @@
-pub mod cli;
\ No newline at end of file
+use std::fmt::Display;
+use anyhow::Result;
+use serde::Serialize;
+
+pub mod cli;
+pub mod validate;
+pub mod probe;
+pub mod plugin;
+pub mod schema;
+
+pub fn print_output<T: Serialize + Display>(output: &T, json: bool) -> Result<()> {
+    match json {
+        true => println!("{}", serde_json::to_string_pretty(output)?),
+        false => println!("{}", output),
+    }
+    Ok(())
+}
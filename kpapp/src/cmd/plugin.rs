@@ -0,0 +1,76 @@
+use std::collections::BTreeMap;
+use std::fmt;
+use std::fmt::{Display, Formatter};
+use std::path::PathBuf;
+use anyhow::{anyhow, Result};
+use kpscene::scene::engine::wasm::KPEngine;
+use serde::Serialize;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct KPAppPluginFilter {
+    pub name: String,
+    pub filter_name: String,
+    pub arguments: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct KPAppPluginInfo {
+    pub path: PathBuf,
+    pub app: String,
+    pub author: String,
+    pub version: String,
+    pub media_type: String,
+    pub sort_type: String,
+    pub groups: Vec<Vec<KPAppPluginFilter>>,
+    pub default_arguments: BTreeMap<String, String>,
+    pub allow_arguments: Vec<String>,
+}
+
+impl Display for KPAppPluginInfo {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        writeln!(f, "path: {}", self.path.display())?;
+        writeln!(f, "app: {}", self.app)?;
+        writeln!(f, "author: {}", self.author)?;
+        writeln!(f, "version: {}", self.version)?;
+        writeln!(f, "media_type: {}", self.media_type)?;
+        writeln!(f, "sort_type: {}", self.sort_type)?;
+        for (index, group) in self.groups.iter().enumerate() {
+            writeln!(f, "group #{}:", index)?;
+            for filter in group.iter() {
+                writeln!(f, "  {} ({}): {}", filter.name, filter.filter_name, filter.arguments)?;
+            }
+        }
+        for argument in self.allow_arguments.iter() {
+            match self.default_arguments.get(argument) {
+                None => writeln!(f, "argument: {}", argument)?,
+                Some(value) => writeln!(f, "argument: {} (default: {})", argument, value)?,
+            }
+        }
+        Ok(())
+    }
+}
+
+pub async fn plugin_info(path: PathBuf) -> Result<KPAppPluginInfo> {
+    let engine = KPEngine::new_with_file(path.clone(), BTreeMap::new()).await
+        .map_err(|err| anyhow!("load plugin failed. path: {}, error: {}", path.display(), err))?;
+
+    let groups = engine.groups.iter().map(|group| {
+        group.iter().map(|filter| KPAppPluginFilter {
+            name: filter.get_name().clone(),
+            filter_name: filter.get_filter_name().clone(),
+            arguments: filter.format_arguments(),
+        }).collect()
+    }).collect();
+
+    Ok(KPAppPluginInfo {
+        path,
+        app: engine.app.clone(),
+        author: engine.author.clone(),
+        version: format!("{:?}", engine.version),
+        media_type: engine.media_type.to_string(),
+        sort_type: format!("{:?}", engine.sort_type).to_lowercase(),
+        groups,
+        default_arguments: engine.default_arguments.clone(),
+        allow_arguments: engine.allow_arguments.clone(),
+    })
+}
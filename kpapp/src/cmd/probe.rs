@@ -0,0 +1,101 @@
+use std::collections::BTreeMap;
+use std::fmt;
+use std::fmt::{Display, Formatter};
+use anyhow::{anyhow, Result};
+use kpcodec::decode::decode::KPDecode;
+use kpcodec::util::alias::KPAVMediaType;
+use serde::Serialize;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct KPAppProbeStream {
+    pub index: usize,
+    pub media_type: String,
+    pub codec: String,
+    pub bit_rate: u64,
+    pub time_base: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub width: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub height: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sample_rate: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub channels: Option<usize>,
+    pub metadata: BTreeMap<String, String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct KPAppProbe {
+    pub path: String,
+    pub format_name: String,
+    pub start_time: f64,
+    pub duration: Option<f64>,
+    pub bit_rate: u64,
+    pub metadata: BTreeMap<String, String>,
+    pub streams: Vec<KPAppProbeStream>,
+}
+
+impl Display for KPAppProbe {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        writeln!(f, "path: {}", self.path)?;
+        writeln!(f, "format: {}", self.format_name)?;
+        writeln!(f, "start_time: {:.3}s", self.start_time)?;
+        match self.duration {
+            None => writeln!(f, "duration: unknown")?,
+            Some(duration) => writeln!(f, "duration: {:.3}s", duration)?,
+        }
+        writeln!(f, "bit_rate: {}", self.bit_rate)?;
+        for (key, value) in self.metadata.iter() {
+            writeln!(f, "metadata: {}={}", key, value)?;
+        }
+        for stream in self.streams.iter() {
+            write!(f, "stream #{}: {}, codec: {}, bit_rate: {}, time_base: {}", stream.index, stream.media_type, stream.codec, stream.bit_rate, stream.time_base)?;
+            if let (Some(width), Some(height)) = (stream.width, stream.height) {
+                write!(f, ", resolution: {}x{}", width, height)?;
+            }
+            if let (Some(sample_rate), Some(channels)) = (stream.sample_rate, stream.channels) {
+                write!(f, ", sample_rate: {}, channels: {}", sample_rate, channels)?;
+            }
+            writeln!(f)?;
+            for (key, value) in stream.metadata.iter() {
+                writeln!(f, "  metadata: {}={}", key, value)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+pub fn probe<T: ToString>(path: T) -> Result<KPAppProbe> {
+    let mut decode = KPDecode::new(path.to_string());
+    decode.open().map_err(|err| anyhow!("open input media file failed. path: {}, error: {}", path.to_string(), err))?;
+    decode.find_streams().map_err(|err| anyhow!("find streams failed. path: {}, error: {}", path.to_string(), err))?;
+
+    let streams = decode.get_streams().iter().map(|(index, stream)| {
+        let media_type = stream.get_media_type();
+        let (width, height) = stream.get_resolution();
+        let is_video = media_type == &KPAVMediaType::KPAVMEDIA_TYPE_VIDEO;
+        let is_audio = media_type == &KPAVMediaType::KPAVMEDIA_TYPE_AUDIO;
+        KPAppProbeStream {
+            index: *index,
+            media_type: media_type.to_string(),
+            codec: stream.get_codec_id().to_string(),
+            bit_rate: stream.get_bit_rate(),
+            time_base: stream.get_time_base().to_string(),
+            width: is_video.then_some(width),
+            height: is_video.then_some(height),
+            sample_rate: is_audio.then_some(stream.get_sample_rate()),
+            channels: is_audio.then_some(stream.get_channels()),
+            metadata: stream.get_metadata().clone(),
+        }
+    }).collect();
+
+    Ok(KPAppProbe {
+        path: path.to_string(),
+        format_name: decode.get_format_name().clone(),
+        start_time: decode.get_start_time().as_secs_f64(),
+        duration: decode.get_duration().map(|duration| duration.as_secs_f64()),
+        bit_rate: decode.get_bit_rate(),
+        metadata: decode.get_metadata().clone(),
+        streams,
+    })
+}
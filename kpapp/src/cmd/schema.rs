@@ -0,0 +1,89 @@
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use anyhow::Result;
+use kpscene::scene::engine::wasm::KPEngine;
+use log::warn;
+use schemars::schema_for;
+use serde_json::{json, Map, Value};
+use crate::util::config::KPAppConfig;
+use crate::util::context::{PLUGIN_EXTENSION, PLUGIN_SUB_PATH};
+
+// scene entries are restricted to the installed plugins and their allowed arguments
+pub async fn config_schema(home_dir: PathBuf) -> Result<Value> {
+    let mut schema = serde_json::to_value(schema_for!(KPAppConfig))?;
+    let plugins = plugin_schemas(&home_dir.join(PLUGIN_SUB_PATH)).await;
+    if !plugins.is_empty() {
+        schema["definitions"]["KPAppPlugin"] = json!({"oneOf": plugins});
+    }
+    Ok(schema)
+}
+
+async fn plugin_schemas(plugin_path: &Path) -> Vec<Value> {
+    let mut paths: Vec<PathBuf> = match std::fs::read_dir(plugin_path) {
+        Ok(entries) => entries.filter_map(|entry| entry.ok().map(|entry| entry.path())).collect(),
+        Err(_) => return vec![],
+    };
+    paths.retain(|path| path.extension().and_then(|extension| extension.to_str()) == Some(PLUGIN_EXTENSION.trim_start_matches('.')));
+    paths.sort();
+
+    let mut plugins = Vec::new();
+    for path in paths {
+        let name = match path.file_stem().and_then(|stem| stem.to_str()) {
+            Some(name) => name.to_string(),
+            None => continue,
+        };
+        let engine = match KPEngine::new_with_file(path.clone(), BTreeMap::new()).await {
+            Ok(engine) => engine,
+            Err(err) => {
+                warn!("load plugin failed, skip in schema. path: {}, error: {}", path.display(), err);
+                continue;
+            }
+        };
+
+        let arguments: Map<String, Value> = engine.allow_arguments.iter().map(|argument| {
+            let mut argument_schema = json!({"type": ["string", "number"]});
+            if let Some(default) = engine.default_arguments.get(argument) {
+                argument_schema["default"] = json!(default);
+            }
+            (argument.clone(), argument_schema)
+        }).collect();
+        plugins.push(json!({
+            "title": engine.app,
+            "type": "object",
+            "required": ["name", "arguments"],
+            "properties": {
+                "name": {"const": name},
+                "arguments": {"type": "object", "properties": arguments, "additionalProperties": false},
+            },
+        }));
+    }
+    plugins
+}
+
+#[cfg(test)]
+mod tests {
+    use std::env;
+    use anyhow::Result;
+    use crate::cmd::schema::config_schema;
+    use crate::util::common::generate_unique_string;
+
+    #[tokio::test]
+    async fn generate_schema() -> Result<()> {
+        let home_dir = env::temp_dir().join(generate_unique_string());
+        let schema = config_schema(home_dir).await?;
+        let definitions = &schema["definitions"];
+
+        // lenient forms
+        assert_eq!(schema["properties"]["output"]["anyOf"][1]["type"], "array");
+        assert_eq!(definitions["KPAppResourceItem"]["anyOf"][0]["type"], "string");
+        assert!(definitions["ResourceItem"]["anyOf"][1]["properties"]["Single"]["properties"]["single"].is_object());
+        assert_eq!(definitions["KPAppPlugin"]["properties"]["arguments"]["additionalProperties"]["type"][1], "number");
+        assert!(definitions["KPAppFailure"]["properties"]["retry_interval"]["anyOf"].is_array());
+
+        // validator constraints
+        assert_eq!(definitions["KPAppServer"]["properties"]["port"]["maximum"], 65535.0);
+        assert_eq!(definitions["KPAppFailure"]["properties"]["retry_count"]["minimum"], 1.0);
+        assert_eq!(definitions["KPAppFailurePolicy"]["enum"][2], "abort");
+        Ok(())
+    }
+}
@@ -0,0 +1,84 @@
+use std::fmt;
+use std::fmt::{Display, Formatter};
+use std::path::PathBuf;
+use kpscene::scene::engine::wasm::KPEngine;
+use serde::Serialize;
+use crate::util::context::KPAppContext;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct KPAppValidatePlugin {
+    pub name: String,
+    pub path: PathBuf,
+    pub valid: bool,
+    pub errors: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct KPAppValidateReport {
+    pub config_path: PathBuf,
+    pub valid: bool,
+    pub errors: Vec<String>,
+    pub plugins: Vec<KPAppValidatePlugin>,
+}
+
+impl Display for KPAppValidateReport {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        writeln!(f, "config: {}", self.config_path.display())?;
+        for err in self.errors.iter() {
+            writeln!(f, "  error: {}", err)?;
+        }
+        for plugin in self.plugins.iter() {
+            writeln!(f, "plugin: {} ({}) {}", plugin.name, plugin.path.display(), if plugin.valid { "ok" } else { "failed" })?;
+            for err in plugin.errors.iter() {
+                writeln!(f, "  error: {}", err)?;
+            }
+        }
+        write!(f, "result: {}", if self.valid { "valid" } else { "invalid" })
+    }
+}
+
+// load the config and every plugin of the scene without starting any stream
+pub async fn validate(home_dir: PathBuf, config_path: PathBuf) -> KPAppValidateReport {
+    let mut report = KPAppValidateReport {
+        config_path: config_path.clone(),
+        valid: false,
+        errors: vec![],
+        plugins: vec![],
+    };
+
+    let context = match KPAppContext::new(home_dir, config_path) {
+        Ok(context) => context,
+        Err(err) => {
+            report.errors.push(err.to_string());
+            return report;
+        }
+    };
+
+    if let Err(err) = context.config.encode.to_encode_parameter() {
+        report.errors.push(format!("create encode parameter failed. error: {}", err));
+    }
+
+    for plugin in context.config.scene.list.iter() {
+        let path = context.plugin_sub_path.join(plugin.name.clone() + &context.plugin_extension);
+        let mut errors = Vec::new();
+        match KPEngine::new_with_file(path.clone(), plugin.arguments.clone()).await {
+            Ok(engine) => {
+                for key in plugin.arguments.keys() {
+                    if !engine.allow_arguments.contains(key) {
+                        errors.push(format!("argument not allowed. argument: {}, allow_arguments: {:?}", key, engine.allow_arguments));
+                    }
+                }
+            }
+            Err(err) => errors.push(format!("load plugin failed. error: {}", err)),
+        }
+        report.plugins.push(KPAppValidatePlugin {
+            name: plugin.name.clone(),
+            path,
+            valid: errors.is_empty(),
+            errors,
+        });
+    }
+
+    report.valid = report.errors.is_empty() && report.plugins.iter().all(|plugin| plugin.valid);
+    report
+}
@@ -1,4 +1,6 @@
 pub mod init;
 pub mod cmd;
 pub mod util;
-pub mod app;
\ No newline at end of file
+pub mod app;
+pub mod notify;
+pub mod api;
@@ -0,0 +1,20 @@
+use log::info;
+use async_trait::async_trait;
+use crate::notify::notifier::KPAppNotifier;
+use crate::util::message::KPAppMessage;
+use kpserver::util::secret::redact;
+
+pub struct KPAppLogNotifier {}
+
+#[async_trait]
+impl KPAppNotifier for KPAppLogNotifier {
+    async fn notify(&self, event: &KPAppMessage) {
+        info!("app event: {}", redact(format!("{:?}", event)));
+    }
+}
+
+impl KPAppLogNotifier {
+    pub fn new() -> Self {
+        KPAppLogNotifier {}
+    }
+}
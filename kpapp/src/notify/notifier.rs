@@ -0,0 +1,7 @@
+use async_trait::async_trait;
+use crate::util::message::KPAppMessage;
+
+#[async_trait]
+pub trait KPAppNotifier: Sync + Send {
+    async fn notify(&self, event: &KPAppMessage);
+}
@@ -1,46 +1,231 @@
-use validator::Validate;
+use std::marker::PhantomData;
+use std::path::Path;
+use std::result::Result as StdResult;
+use serde::de::DeserializeSeed;
+use validator::{Validate, ValidationError, ValidationErrors, ValidationErrorsKind};
 use crate::util::*;
+use crate::util::module::deserialize::locate::{format_path, offset_to_location, KPAppLocateSeed, KPAppPathSegment};
+use crate::util::module::deserialize::list::{one_or_many, one_or_many_schema};
+use crate::util::module::deserialize::interpolate::interpolate;
+use kpserver::util::secret::{redact, register_secret};
+use serde_json::Value;
+use schemars::JsonSchema;
+use crate::util::module::validator::output::validate_unique_outputs;
 
-#[derive(Serialize, Deserialize, Clone, Debug, Validate)]
+#[derive(Serialize, Deserialize, Clone, Debug, Validate, JsonSchema)]
 pub struct KPAppConfig {
     #[validate(nested)]
     pub playlist: KPAppResource,
+    #[serde(deserialize_with = "one_or_many")]
+    #[schemars(schema_with = "one_or_many_schema::<KPAppOutput>")]
+    #[validate(length(min = 1))]
+    #[validate(custom(function = "validate_unique_outputs"))]
     #[validate(nested)]
-    pub output: KPAppOutput,
+    pub output: Vec<KPAppOutput>,
     #[validate(nested)]
     pub scene: KPAppScene,
+    #[serde(default)]
+    #[validate(nested)]
+    pub schedule: Option<KPAppSchedule>,
+    #[serde(default)]
+    #[validate(nested)]
+    pub encode: KPAppEncode,
+    #[serde(default)]
+    #[validate(nested)]
+    pub failure: KPAppFailure,
+    #[serde(default)]
+    #[validate(nested)]
+    pub server: KPAppServer,
+    #[serde(default)]
+    #[validate(nested)]
+    pub api: Option<KPAppApi>,
+    #[serde(default)]
+    #[validate(nested)]
+    pub metrics: Option<KPAppMetrics>,
+    #[serde(default)]
+    #[validate(nested)]
+    pub shutdown: KPAppShutdown,
+    #[serde(default)]
+    #[validate(nested)]
+    pub fallback: Option<KPAppFallback>,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum KPAppConfigFormat {
+    Json,
+    Yaml,
+    Toml,
+}
+
+impl KPAppConfigFormat {
+    pub fn from_path(path: &Path) -> Self {
+        match path.extension().and_then(|extension| extension.to_str()).map(|extension| extension.to_lowercase()) {
+            Some(extension) if extension == "yaml" || extension == "yml" => KPAppConfigFormat::Yaml,
+            Some(extension) if extension == "toml" => KPAppConfigFormat::Toml,
+            _ => KPAppConfigFormat::Json,
+        }
+    }
+
+    fn deserialize<'de, T: DeserializeSeed<'de>>(&self, content: &'de str, seed: T) -> StdResult<T::Value, (String, Option<(usize, usize)>)> {
+        match self {
+            KPAppConfigFormat::Json => {
+                let mut deserializer = serde_json::Deserializer::from_str(content);
+                seed.deserialize(&mut deserializer).map_err(|err| {
+                    let location = Some((err.line(), err.column())).filter(|(line, _)| *line > 0);
+                    (err.to_string(), location)
+                })
+            }
+            KPAppConfigFormat::Yaml => {
+                seed.deserialize(serde_yaml::Deserializer::from_str(content)).map_err(|err| {
+                    let location = err.location().map(|location| (location.line(), location.column()));
+                    (err.to_string(), location)
+                })
+            }
+            KPAppConfigFormat::Toml => {
+                seed.deserialize(toml::Deserializer::new(content)).map_err(|err| {
+                    let location = err.span().map(|span| offset_to_location(content, span.start));
+                    (err.message().to_string(), location)
+                })
+            }
+        }
+    }
+
+    // line and column of the value at the path
+    fn locate(&self, content: &str, path: &[KPAppPathSegment]) -> Option<(usize, usize)> {
+        match self.deserialize(content, KPAppLocateSeed::new(path)) {
+            Ok(_) => None,
+            Err((_, location)) => location,
+        }
+    }
 }
 
 impl KPAppConfig {
     pub fn from_json_str(json_str: String) -> Result<KPAppConfig> {
-        let cfg: KPAppConfig = serde_json::from_str(json_str.as_str())?;
-        cfg.validate()?;
+        Self::from_str(json_str.as_str(), &KPAppConfigFormat::Json, None)
+    }
+
+    pub fn from_file(path: &Path) -> Result<KPAppConfig> {
+        let content = std::fs::read_to_string(path).map_err(|err| anyhow!("read config file failed. path: {}, error: {}", path.display(), err))?;
+        Self::from_str(content.as_str(), &KPAppConfigFormat::from_path(path), Some(path))
+    }
+
+    pub fn from_str(content: &str, format: &KPAppConfigFormat, path: Option<&Path>) -> Result<KPAppConfig> {
+        let source = |location: Option<(usize, usize)>| match (path, location) {
+            (Some(path), Some((line, column))) => format!("{}:{}:{}", path.display(), line, column),
+            (None, Some((line, column))) => format!("line {} column {}", line, column),
+            (Some(path), None) => path.display().to_string(),
+            (None, None) => "unknown".to_string(),
+        };
+
+        // interpolate variables and secret files before the typed parse and the validators
+        let mut value: Value = format.deserialize(content, PhantomData).map_err(|(err, location)| {
+            anyhow!("parse config failed. source: {}, error: {}", source(location), err)
+        })?;
+        interpolate(&mut value).map_err(|(field_path, err)| {
+            anyhow!("interpolate config failed. source: {}, field: {}, error: {}", source(format.locate(content, &field_path)), format_path(&field_path), err)
+        })?;
+        let cfg: KPAppConfig = match serde_json::from_value(value) {
+            Ok(cfg) => cfg,
+            Err(err) => {
+                // the raw document fails the same way and carries the line and column
+                let (err, location) = match format.deserialize(content, PhantomData::<KPAppConfig>) {
+                    Ok(_) => (err.to_string(), None),
+                    Err(err) => err,
+                };
+                return Err(anyhow!("parse config failed. source: {}, error: {}", source(location), redact(err)));
+            }
+        };
+        if let Some(token) = cfg.api.as_ref().and_then(|api| api.token.as_ref()) {
+            register_secret(token);
+        }
+
+        if let Err(errors) = cfg.validate() {
+            let mut messages = Vec::new();
+            for (field_path, error) in flatten_errors(&errors, Vec::new()) {
+                let message = error.message.as_ref().map(|message| message.to_string()).unwrap_or(error.code.to_string());
+                messages.push(format!("source: {}, field: {}, error: {}", source(format.locate(content, &field_path)), format_path(&field_path), message));
+            }
+            return Err(anyhow!("validate config failed. {}", redact(messages.join("; "))));
+        }
         Ok(cfg)
     }
 }
 
+// schema level errors point at the struct itself
+fn flatten_errors(errors: &ValidationErrors, path: Vec<KPAppPathSegment>) -> Vec<(Vec<KPAppPathSegment>, ValidationError)> {
+    let mut result = Vec::new();
+    for (field, kind) in errors.errors() {
+        let mut field_path = path.clone();
+        if *field != "__all__" {
+            field_path.push(KPAppPathSegment::Key(field.to_string()));
+        }
+        match kind {
+            ValidationErrorsKind::Field(field_errors) => {
+                for error in field_errors {
+                    result.push((field_path.clone(), error.clone()));
+                }
+            }
+            ValidationErrorsKind::Struct(struct_errors) => result.extend(flatten_errors(struct_errors, field_path)),
+            ValidationErrorsKind::List(list_errors) => {
+                for (index, item_errors) in list_errors {
+                    let mut item_path = field_path.clone();
+                    item_path.push(KPAppPathSegment::Index(*index));
+                    result.extend(flatten_errors(item_errors, item_path));
+                }
+            }
+        }
+    }
+    result
+}
+
 #[cfg(test)]
 mod tests {
     use std::env;
     use std::path::PathBuf;
+    use std::time::Duration;
     use crate::init::initialize;
-    use crate::util::config::KPAppConfig;
-    use crate::util::module::resource::{KPAppResource, KPAppResourceItem, SingleDetail};
+    use crate::util::config::{KPAppConfig, KPAppConfigFormat};
+    use crate::util::common::generate_unique_string;
+    use std::path::Path;
+    use crate::util::module::resource::{KPAppResource, KPAppResourceItem, KPAppResourceMode, SingleDetail};
     use crate::util::module::resource::ResourceItem::Single;
+    use crate::util::module::resource::{DirectorySort, MixEndWith, ResourceItem};
+    use crate::util::module::validator::resource::validate_trim;
+    use crate::util::module::validator::file::playlist_extension as validate_playlist_extension;
+    use validator::Validate;
     use anyhow::{anyhow, Result};
     use log::info;
     use crate::util::context::KPAppContext;
     use crate::util::module::output::KPAppOutput;
     use crate::util::module::scene::{KPAppPlugin, KPAppScene};
+    use crate::util::module::schedule::{KPAppSchedule, KPAppSchedulePolicy};
+    use crate::util::module::encode::KPAppEncode;
+    use crate::util::module::failure::{KPAppFailure, KPAppFailurePolicy};
+    use crate::util::module::server::KPAppServer;
+    use crate::util::module::api::KPAppApi;
+    use crate::util::module::metrics::KPAppMetrics;
+    use crate::util::module::shutdown::KPAppShutdown;
+    use crate::util::module::fallback::KPAppFallback;
+    use crate::util::module::deserialize::interpolate::interpolate_str;
+    use kpserver::util::secret::redact;
+    use crate::util::module::validator::output::validate_unique_outputs;
 
     #[test]
     fn output_json() -> Result<()> {
         initialize();
 
         let context = KPAppConfig {
-            playlist: KPAppResource { name: "default_playlist".to_string(), list: vec![KPAppResourceItem { name: "default_media".to_string(), resource: Single { single: SingleDetail { path: "media_path".to_string(), expect_streams: Default::default() } } }] },
-            output: KPAppOutput { name: "default_output".to_string(), path: "rtmp://127.0.0.1:1935/live/test".to_string() },
+            playlist: KPAppResource { name: "default_playlist".to_string(), mode: Default::default(), list: vec![KPAppResourceItem { name: "default_media".to_string(), resource: Single { single: SingleDetail { path: "media_path".to_string(), expect_streams: Default::default(), start: None, end: None, duration: None, metadata: Default::default() } }, scene: None }] },
+            output: vec![KPAppOutput::new("default_output", "rtmp://127.0.0.1:1935/live/test")],
             scene: KPAppScene { name: "default_scene".to_string(), list: vec![KPAppPlugin { name: "text".to_string(), arguments: Default::default() }] },
+            schedule: None,
+            encode: KPAppEncode::default(),
+            failure: KPAppFailure::default(),
+            server: KPAppServer::default(),
+            api: None,
+            metrics: None,
+            shutdown: KPAppShutdown::default(),
+            fallback: None,
         };
 
         let json_str = serde_json::to_string(&context)?;
@@ -65,4 +250,416 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn parse_resource_item() -> Result<()> {
+        let item: KPAppResourceItem = serde_json::from_str(r#"{"name": "mix", "resource": {"Mix": {"mix": {"video": "video.mp4", "audio": "music.mp3", "end_with": "video"}}}}"#)?;
+        match item.resource {
+            ResourceItem::Mix { mix } => assert_eq!(mix.end_with, MixEndWith::Video),
+            _ => return Err(anyhow!("parse mix resource failed")),
+        }
+
+        let item: KPAppResourceItem = serde_json::from_str(r#"{"name": "dir", "resource": {"Directory": {"directory": {"path": "/tmp", "sort": "mtime"}}}}"#)?;
+        match item.resource {
+            ResourceItem::Directory { directory } => assert_eq!(directory.sort, DirectorySort::Mtime),
+            _ => return Err(anyhow!("parse directory resource failed")),
+        }
+
+        let item: KPAppResourceItem = serde_json::from_str(r#"{"name": "m3u", "resource": {"Playlist": {"playlist": {"path": "/tmp/list.m3u8"}}}}"#)?;
+        match item.resource {
+            ResourceItem::Playlist { playlist } => {
+                assert_eq!(playlist.path, "/tmp/list.m3u8");
+                assert!(validate_playlist_extension("/tmp/list.PLS").is_ok());
+                assert!(validate_playlist_extension("/tmp/list.txt").is_err());
+            }
+            _ => return Err(anyhow!("parse playlist resource failed")),
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn parse_trim() -> Result<()> {
+        let single: SingleDetail = serde_json::from_str(r#"{"path": "media.mp4", "expect_streams": {}, "start": "00:01:30.5", "duration": 60}"#)?;
+        assert_eq!(single.start, Some(Duration::from_millis(90500)));
+        assert_eq!(single.get_end_point(), Some(Duration::from_millis(150500)));
+        assert!(validate_trim(&single).is_ok());
+
+        let single: SingleDetail = serde_json::from_str(r#"{"path": "media.mp4", "expect_streams": {}, "start": 30, "end": 10}"#)?;
+        assert!(validate_trim(&single).is_err());
+        assert!(serde_json::from_str::<SingleDetail>(r#"{"path": "media.mp4", "expect_streams": {}, "start": "1:xx"}"#).is_err());
+
+        // start plus duration overflows
+        let single: SingleDetail = serde_json::from_str(r#"{"path": "media.mp4", "expect_streams": {}, "start": "18000000000000000000", "duration": "18000000000000000000"}"#)?;
+        assert_eq!(single.get_end_point(), None);
+        assert_eq!(validate_trim(&single).unwrap_err().code, "trim_overflow");
+
+        // a local single file is checked on disk, a url only for its trim
+        let item: KPAppResourceItem = serde_json::from_str(r#"{"name": "missing", "resource": {"Single": {"single": {"path": "/not/exist.mp4", "expect_streams": {}}}}}"#)?;
+        assert!(item.validate().is_err());
+        let item: KPAppResourceItem = serde_json::from_str(r#"{"name": "url", "resource": {"Single": {"single": {"path": "http://127.0.0.1/media.mov", "expect_streams": {}}}}}"#)?;
+        assert!(item.validate().is_ok());
+        let item: KPAppResourceItem = serde_json::from_str(r#"{"name": "trim", "resource": {"Single": {"single": {"path": "http://127.0.0.1/media.mp4", "expect_streams": {}, "start": 30, "end": 10}}}}"#)?;
+        assert!(item.validate().is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn parse_invalid_duration() -> Result<()> {
+        for start in [r#""nan""#, r#""inf""#, r#""-1""#, r#""1e30""#, r#""99999999999999999999:00""#, "1e30", "-1"] {
+            let json = format!(r#"{{"path": "media.mp4", "expect_streams": {{}}, "start": {}}}"#, start);
+            assert!(serde_json::from_str::<SingleDetail>(&json).is_err(), "{}", start);
+        }
+
+        // non-finite yaml numbers become null in a json value
+        let yaml = "path: media.mp4\nexpect_streams: {}\nduration: .inf\n";
+        assert!(serde_yaml::from_str::<SingleDetail>(yaml).map_or(true, |single| single.duration.is_none()));
+        Ok(())
+    }
+
+    #[test]
+    fn parse_network() -> Result<()> {
+        let item: KPAppResourceItem = serde_json::from_str(r#"{"name": "relay", "resource": {"Network": {"network": {"url": "http://127.0.0.1:8080/live/index.m3u8", "read_timeout": 5}}}}"#)?;
+        let network = match &item.resource {
+            ResourceItem::Network { network } => network.clone(),
+            _ => return Err(anyhow!("parse network resource failed")),
+        };
+        assert!(item.validate().is_ok());
+        assert_eq!(network.read_timeout, Duration::from_secs(5));
+        assert_eq!(network.reconnect.get_interval(0), Duration::from_secs(1));
+        assert_eq!(network.reconnect.get_interval(3), Duration::from_secs(8));
+        assert_eq!(network.reconnect.get_interval(10), Duration::from_secs(30));
+
+        // drops spread over a long run do not use up the retries
+        assert_eq!(network.reconnect.next_attempt(4, Duration::from_secs(5)), 4);
+        assert_eq!(network.reconnect.next_attempt(4, Duration::from_secs(3600)), 0);
+
+        let item: KPAppResourceItem = serde_json::from_str(r#"{"name": "relay", "resource": {"Network": {"network": {"url": "/data/live.flv"}}}}"#)?;
+        assert!(item.validate().is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn parse_schedule() -> Result<()> {
+        let schedule: KPAppSchedule = serde_json::from_str(r#"{"list": [{"name": "news", "start": "08:00", "policy": "hard_cut", "list": []}, {"name": "film", "cron": "0 0 20 * * *", "list": []}]}"#)?;
+        assert!(schedule.validate().is_ok());
+        assert_eq!(schedule.list[0].policy, KPAppSchedulePolicy::HardCut);
+        assert_eq!(schedule.list[1].policy, KPAppSchedulePolicy::Wait);
+        assert!(schedule.fallback.is_none());
+
+        let invalid = [
+            r#"{"list": [{"name": "news", "list": []}]}"#,
+            r#"{"list": [{"name": "news", "start": "08:00", "cron": "0 0 8 * * *", "list": []}]}"#,
+            r#"{"list": [{"name": "news", "start": "25:00", "list": []}]}"#,
+            r#"{"list": [{"name": "news", "cron": "every day", "list": []}]}"#,
+            r#"{"list": [{"name": "news", "start": "08:00", "list": []}, {"name": "news", "start": "09:00", "list": []}]}"#,
+        ];
+        for json in invalid {
+            let schedule: KPAppSchedule = serde_json::from_str(json)?;
+            assert!(schedule.validate().is_err(), "{}", json);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn parse_encode() -> Result<()> {
+        let encode: KPAppEncode = serde_json::from_str(r#"{"video": {"width": 1280, "height": 720, "framerate": 30, "max_bitrate": 2500000, "preset": "fast"}}"#)?;
+        assert!(encode.validate().is_ok());
+        assert_eq!(encode.video.codec, "h264");
+        assert_eq!(encode.video.gop, 2);
+        assert_eq!(encode.audio.sample_rate, 48000);
+        assert_eq!(encode.to_encode_parameter()?.len(), 2);
+
+        let invalid = [
+            r#"{"video": {"width": 0}}"#,
+            r#"{"video": {"framerate": 240}}"#,
+            r#"{"video": {"preset": "instant"}}"#,
+            r#"{"audio": {"channels": 0}}"#,
+        ];
+        for json in invalid {
+            let encode: KPAppEncode = serde_json::from_str(json)?;
+            assert!(encode.validate().is_err(), "{}", json);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn parse_failure() -> Result<()> {
+        let failure: KPAppFailure = serde_json::from_str(r#"{}"#)?;
+        assert_eq!(failure.policy, KPAppFailurePolicy::Skip);
+        assert_eq!(failure.retry_count, 3);
+
+        let failure: KPAppFailure = serde_json::from_str(r#"{"policy": "retry", "retry_count": 5, "retry_interval": 2}"#)?;
+        assert!(failure.validate().is_ok());
+        assert_eq!(failure.policy, KPAppFailurePolicy::Retry);
+        assert_eq!(failure.retry_interval, Duration::from_secs(2));
+
+        let failure: KPAppFailure = serde_json::from_str(r#"{"policy": "retry", "retry_count": 0}"#)?;
+        assert!(failure.validate().is_err());
+        assert!(serde_json::from_str::<KPAppFailure>(r#"{"policy": "ignore"}"#).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn parse_server() -> Result<()> {
+        let server: KPAppServer = serde_json::from_str(r#"{}"#)?;
+        assert!(server.validate().is_ok());
+        assert_eq!(server.port, 1935);
+        assert_eq!(server.gop_number, 29);
+        assert_eq!(server.get_local_url("app", "core"), "rtmp://127.0.0.1:1935/app/core");
+
+        let server: KPAppServer = serde_json::from_str(r#"{"address": "::", "port": 11935, "httpflv": {"port": 8080}, "hls": {"port": 8081}}"#)?;
+        assert!(server.validate().is_ok());
+        assert_eq!(server.get_local_url("app", "core"), "rtmp://[::1]:11935/app/core");
+
+        let server: KPAppServer = serde_json::from_str(r#"{"address": "192.168.1.10"}"#)?;
+        assert_eq!(server.get_local_url("app", "core"), "rtmp://192.168.1.10:1935/app/core");
+
+        let invalid = [
+            r#"{"port": 0}"#,
+            r#"{"httpflv": {"port": 1935}}"#,
+            r#"{"httpflv": {"port": 8080}, "hls": {"port": 8080}}"#,
+        ];
+        for json in invalid {
+            let server: KPAppServer = serde_json::from_str(json)?;
+            assert!(server.validate().is_err(), "{}", json);
+        }
+        assert!(serde_json::from_str::<KPAppServer>(r#"{"address": "localhost"}"#).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn parse_api() -> Result<()> {
+        let api: KPAppApi = serde_json::from_str(r#"{}"#)?;
+        assert!(api.validate().is_ok());
+        assert_eq!(api.address.to_string(), "127.0.0.1");
+        assert_eq!(api.port, 8000);
+
+        let api: KPAppApi = serde_json::from_str(r#"{"address": "0.0.0.0", "port": 9000, "token": "secret"}"#)?;
+        assert!(api.validate().is_ok());
+        assert_eq!(api.token, Some("secret".to_string()));
+
+        let api: KPAppApi = serde_json::from_str(r#"{"token": ""}"#)?;
+        assert!(api.validate().is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn parse_metrics() -> Result<()> {
+        let metrics: KPAppMetrics = serde_json::from_str(r#"{}"#)?;
+        assert!(metrics.validate().is_ok());
+        assert_eq!(metrics.address.to_string(), "127.0.0.1");
+        assert_eq!(metrics.port, 9100);
+
+        let metrics: KPAppMetrics = serde_json::from_str(r#"{"port": 0}"#)?;
+        assert!(metrics.validate().is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn parse_shutdown() -> Result<()> {
+        let shutdown: KPAppShutdown = serde_json::from_str(r#"{}"#)?;
+        assert_eq!(shutdown.timeout, Duration::from_secs(10));
+
+        let shutdown: KPAppShutdown = serde_json::from_str(r#"{"timeout": "00:00:30"}"#)?;
+        assert_eq!(shutdown.timeout, Duration::from_secs(30));
+        assert!(serde_json::from_str::<KPAppShutdown>(r#"{"timeout": -1}"#).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn parse_interpolate() -> Result<()> {
+        let key_path = env::temp_dir().join(format!("{}.key", generate_unique_string()));
+        std::fs::write(&key_path, "file_stream_key\n")?;
+        env::set_var("KPLAYER_TEST_STREAM_KEY", "env_stream_key");
+        env::set_var("KPLAYER_TEST_APP", "public_application");
+        env::remove_var("KPLAYER_TEST_UNSET");
+
+        let json = format!(r#"{{
+            "playlist": {{"name": "playlist", "list": []}},
+            "output": [
+                {{"name": "env", "path": "rtmp://127.0.0.1:1935/live/${{secret:KPLAYER_TEST_STREAM_KEY}}"}},
+                {{"name": "default", "path": "rtmp://127.0.0.1:1935/${{KPLAYER_TEST_UNSET:-live}}/test"}},
+                {{"name": "app", "path": "rtmp://127.0.0.1:1935/${{KPLAYER_TEST_APP}}/test"}},
+                {{"name": "file", "path": "@file:{}"}}
+            ],
+            "scene": {{"name": "scene", "list": []}}
+        }}"#, key_path.display());
+        let err = KPAppConfig::from_str(&json, &KPAppConfigFormat::Json, None).unwrap_err().to_string();
+        assert!(err.contains("output[3].path"), "{}", err);
+        assert!(!err.contains("file_stream_key"), "{}", err);
+
+        let json = json.replace("@file:", "rtmp://127.0.0.1:1935/live/@file:");
+        let config = KPAppConfig::from_str(&json, &KPAppConfigFormat::Json, None)?;
+        assert_eq!(config.output[0].path, "rtmp://127.0.0.1:1935/live/env_stream_key");
+        assert_eq!(config.output[1].path, "rtmp://127.0.0.1:1935/live/test");
+        assert_eq!(redact(&config.output[0].path), "rtmp://127.0.0.1:1935/live/******");
+
+        // plain variables are not secrets
+        assert_eq!(redact(&config.output[2].path), "rtmp://127.0.0.1:1935/public_application/test");
+
+        assert_eq!(interpolate_str(&format!("@file:{}", key_path.display())).unwrap(), "file_stream_key");
+        assert_eq!(interpolate_str("$${KPLAYER_TEST_STREAM_KEY}").unwrap(), "${KPLAYER_TEST_STREAM_KEY}");
+        assert!(interpolate_str("${KPLAYER_TEST_UNSET}").is_err());
+        assert!(interpolate_str("${KPLAYER_TEST_STREAM_KEY").is_err());
+        assert!(interpolate_str("@file:/not/exist.key").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn parse_scene_override() -> Result<()> {
+        let item: KPAppResourceItem = serde_json::from_str(r#"{"name": "ep1", "resource": "ep1.mp4", "scene": {"remove": ["logo"], "arguments": [{"name": "text", "arguments": {"content": "Episode 1", "size": 24}}], "add": [{"name": "subtitle", "arguments": {}}]}}"#)?;
+        let scene = KPAppScene {
+            name: "default_scene".to_string(),
+            list: vec![
+                KPAppPlugin { name: "logo".to_string(), arguments: Default::default() },
+                KPAppPlugin { name: "text".to_string(), arguments: [("content".to_string(), "kplayer".to_string()), ("color".to_string(), "white".to_string())].into() },
+            ],
+        };
+        let merged = scene.merge(item.scene.as_ref().ok_or_else(|| anyhow!("parse scene override failed"))?);
+        assert_eq!(merged.list.iter().map(|plugin| plugin.name.as_str()).collect::<Vec<_>>(), vec!["text", "subtitle"]);
+        assert_eq!(merged.list[0].arguments.get("content").map(|content| content.as_str()), Some("Episode 1"));
+        assert_eq!(merged.list[0].arguments.get("size").map(|size| size.as_str()), Some("24"));
+        assert_eq!(merged.list[0].arguments.get("color").map(|color| color.as_str()), Some("white"));
+
+        // items without overrides keep the global scene
+        let item: KPAppResourceItem = serde_json::from_str(r#""ep2.mp4""#)?;
+        assert!(item.scene.is_none());
+        assert!(!serde_json::to_string(&item)?.contains("scene"));
+        Ok(())
+    }
+
+    #[test]
+    fn parse_fallback() -> Result<()> {
+        let dir = env::temp_dir().join(generate_unique_string());
+        std::fs::create_dir_all(&dir)?;
+        let image_path = dir.join("right:back,now.png");
+        std::fs::write(&image_path, "")?;
+
+        let fallback: KPAppFallback = serde_json::from_value(serde_json::json!({"path": image_path, "scene": {"add": [{"name": "text", "arguments": {"content": "we'll be right back"}}]}}))?;
+        assert!(fallback.validate().is_ok());
+        assert_eq!(fallback.scene.as_ref().map(|scene| scene.add.len()), Some(1));
+        let graph = fallback.to_filter_graph(&KPAppEncode::default());
+        let movie = format!(r#"movie=''\''{}'\'''"#, image_path.display());
+        assert_eq!(graph, format!("{},loop=loop=-1:size=1,setpts=N/29/TB[out0];anullsrc=r=48000:cl=stereo[out1]", movie));
+
+        let fallback: KPAppFallback = serde_json::from_str(r#"{"path": "/not/exist.mp4"}"#)?;
+        assert!(fallback.validate().is_err());
+        assert!(fallback.to_filter_graph(&KPAppEncode::default()).contains(":loop=0,setpts=N/FRAME_RATE/TB[out0]"));
+
+        let slate_path = dir.join("slate.txt");
+        std::fs::write(&slate_path, "")?;
+        let fallback: KPAppFallback = serde_json::from_value(serde_json::json!({"path": slate_path}))?;
+        assert!(fallback.validate().is_err());
+
+        std::fs::remove_dir_all(dir)?;
+        Ok(())
+    }
+
+    #[test]
+    fn parse_mode() -> Result<()> {
+        let resource: KPAppResource = serde_json::from_str(r#"{"name": "default_playlist", "list": []}"#)?;
+        assert_eq!(resource.mode, KPAppResourceMode::Sequence);
+
+        for (name, mode) in [
+            ("sequence", KPAppResourceMode::Sequence),
+            ("repeat_all", KPAppResourceMode::RepeatAll),
+            ("repeat_one", KPAppResourceMode::RepeatOne),
+            ("shuffle", KPAppResourceMode::Shuffle),
+            ("shuffle_no_repeat", KPAppResourceMode::ShuffleNoRepeat),
+        ] {
+            let resource: KPAppResource = serde_json::from_str(&format!(r#"{{"name": "default_playlist", "mode": "{}", "list": []}}"#, name))?;
+            assert_eq!(resource.mode, mode);
+        }
+        assert!(serde_json::from_str::<KPAppResource>(r#"{"name": "default_playlist", "mode": "random", "list": []}"#).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn parse_output() -> Result<()> {
+        let config: Vec<KPAppOutput> = serde_json::from_str(r#"[{"name": "youtube", "path": "rtmp://a.rtmp.youtube.com/live2/key"}, {"name": "twitch", "path": "rtmp://live.twitch.tv/app/key", "retry_interval": null}]"#)?;
+        assert_eq!(config[0].retry_interval, Some(Duration::from_secs(5)));
+        assert_eq!(config[1].retry_interval, None);
+        assert!(validate_unique_outputs(&config).is_ok());
+
+        let config = vec![KPAppOutput::new("push", "rtmp://127.0.0.1/live/a"), KPAppOutput::new("push", "rtmp://127.0.0.1/live/b")];
+        assert!(validate_unique_outputs(&config).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn parse_yaml_and_toml() -> Result<()> {
+        let media_path = env::temp_dir().join(format!("{}.mp4", generate_unique_string()));
+        std::fs::write(&media_path, "")?;
+        let media_path = media_path.to_string_lossy().to_string();
+
+        let yaml = format!(r#"
+# playlist with a bare string shorthand
+playlist:
+  name: default_playlist
+  mode: repeat_all
+  list:
+    - {}
+output:
+  name: push
+  path: rtmp://127.0.0.1:1935/live/test
+scene:
+  name: default_scene
+  list:
+    - name: text
+      arguments:
+        text: hello
+        size: 12
+"#, media_path);
+        let config = KPAppConfig::from_str(&yaml, &KPAppConfigFormat::Yaml, None)?;
+        assert_eq!(config.playlist.mode, KPAppResourceMode::RepeatAll);
+        assert_eq!(config.output.len(), 1);
+        assert_eq!(config.scene.list[0].arguments.get("size"), Some(&"12".to_string()));
+
+        let toml = format!(r#"
+[playlist]
+name = "default_playlist"
+list = ["{}"]
+
+[[output]]
+name = "push"
+path = "rtmp://127.0.0.1:1935/live/test"
+
+[scene]
+name = "default_scene"
+list = []
+"#, media_path);
+        let config = KPAppConfig::from_str(&toml, &KPAppConfigFormat::Toml, None)?;
+        assert_eq!(config.playlist.list.len(), 1);
+        std::fs::remove_file(&media_path)?;
+
+        // validation error points at the source line
+        let yaml = r#"playlist:
+  name: default_playlist
+  list:
+    - name: movie
+      resource:
+        Directory:
+          directory:
+            path: /not/exist
+output:
+  - name: push
+    path: http://127.0.0.1/live/test
+scene:
+  name: default_scene
+  list: []
+"#;
+        let err = KPAppConfig::from_str(yaml, &KPAppConfigFormat::Yaml, Some(Path::new("kplayer.yaml"))).unwrap_err().to_string();
+        info!("{}", err);
+        assert!(err.contains("kplayer.yaml:8:"), "{}", err);
+        assert!(err.contains("kplayer.yaml:11:"), "{}", err);
+
+        let json = "{\n  \"playlist\": {\"name\": \"p\", \"list\": []},\n  \"output\": [],\n  \"scene\": {\"name\": \"s\", \"list\": []}\n}";
+        let err = KPAppConfig::from_json_str(json.to_string()).unwrap_err().to_string();
+        assert!(err.contains("line 3"), "{}", err);
+
+        assert_eq!(KPAppConfigFormat::from_path(Path::new("/etc/kplayer.YML")), KPAppConfigFormat::Yaml);
+        assert_eq!(KPAppConfigFormat::from_path(Path::new("kplayer.toml")), KPAppConfigFormat::Toml);
+        assert_eq!(KPAppConfigFormat::from_path(Path::new("kplayer.json")), KPAppConfigFormat::Json);
+        Ok(())
+    }
 }
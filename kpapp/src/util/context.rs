@@ -4,6 +4,10 @@ use crate::init::initialize;
 use crate::util::*;
 use crate::util::common::generate_unique_string;
 
+const TEMPORARILY_SERVER_STREAM: &str = "core";
+pub const PLUGIN_SUB_PATH: &str = "plugin";
+pub const PLUGIN_EXTENSION: &str = ".kpe";
+
 #[derive(Clone, Debug)]
 pub struct KPAppContext {
     pub home_dir: PathBuf,
@@ -12,6 +16,9 @@ pub struct KPAppContext {
     pub plugin_extension: String,
     pub config: KPAppConfig,
 
+    // options
+    pub ignore_state: bool,
+
     // state
     pub temporarily_server_app: String,
 }
@@ -19,22 +26,30 @@ pub struct KPAppContext {
 impl KPAppContext {
     pub fn new(home_dir: PathBuf, config_path: PathBuf) -> Result<Self> {
         if !config_path.exists() {
-            return Err(anyhow!("The kplayer.json file does not exist at the provided home path. file_path: {}", config_path.display()));
+            return Err(anyhow!("The config file does not exist at the provided home path. file_path: {}", config_path.display()));
         }
-        let kplayer_json_content = std::fs::read_to_string(&config_path)
-            .map_err(|e| anyhow!("Failed to read kplayer.json file: {}", e))?;
-        let config = KPAppConfig::from_json_str(kplayer_json_content)?;
+        let config = KPAppConfig::from_file(&config_path)?;
 
         // context
         Ok(KPAppContext {
-            plugin_sub_path: home_dir.join("plugin"),
-            plugin_extension: ".kpe".to_string(),
+            plugin_sub_path: home_dir.join(PLUGIN_SUB_PATH),
+            plugin_extension: PLUGIN_EXTENSION.to_string(),
             home_dir,
             config_path,
             config,
+            ignore_state: false,
             temporarily_server_app: generate_unique_string(),
         })
     }
+
+    pub fn get_temporarily_server_stream(&self) -> String {
+        TEMPORARILY_SERVER_STREAM.to_string()
+    }
+
+    // the transcode publishes once to the core server, every output relays from there
+    pub fn get_temporarily_server_url(&self) -> String {
+        self.config.server.get_local_url(self.temporarily_server_app.as_str(), TEMPORARILY_SERVER_STREAM)
+    }
 }
 
 
@@ -1,4 +1,49 @@
+use std::time::Duration;
+use serde::Serialize;
 use strum_macros::Display;
+use crate::util::module::deserialize::duration::{serialize_duration, serialize_option_duration};
 
-#[derive(Clone, Display, Debug)]
-pub enum KPAppMessage {}
\ No newline at end of file
+#[derive(Clone, Display, Debug, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum KPAppMessage {
+    ItemOpened {
+        name: String,
+    },
+    ItemStarted {
+        name: String,
+    },
+    ItemFinished {
+        name: String,
+        #[serde(serialize_with = "serialize_duration")]
+        duration: Duration,
+    },
+    ItemFailed {
+        name: String,
+        error: String,
+    },
+    ItemSkipped {
+        name: String,
+        failures: usize,
+        error: String,
+    },
+    PlaylistWrapped {
+        count: usize,
+    },
+    FallbackStarted {
+        path: String,
+    },
+    FallbackStopped {
+        #[serde(serialize_with = "serialize_duration")]
+        duration: Duration,
+    },
+    Progress {
+        name: String,
+        #[serde(serialize_with = "serialize_option_duration")]
+        position: Option<Duration>,
+        fps: f64,
+        speed: f64,
+    },
+    AppClosed {
+        error: Option<String>,
+    },
+}
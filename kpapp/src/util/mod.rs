@@ -2,6 +2,14 @@ use anyhow::Result;
 use crate::util::module::output::KPAppOutput;
 use crate::util::module::resource::KPAppResource;
 use crate::util::module::scene::KPAppScene;
+use crate::util::module::schedule::KPAppSchedule;
+use crate::util::module::encode::KPAppEncode;
+use crate::util::module::failure::KPAppFailure;
+use crate::util::module::server::KPAppServer;
+use crate::util::module::api::KPAppApi;
+use crate::util::module::metrics::KPAppMetrics;
+use crate::util::module::shutdown::KPAppShutdown;
+use crate::util::module::fallback::KPAppFallback;
 use serde::{Deserialize, Serialize};
 use std::path::PathBuf;
 use anyhow::anyhow;
@@ -11,5 +19,5 @@ pub mod context;
 pub mod config;
 pub mod module;
 pub mod vars;
-mod common;
+pub(crate) mod common;
 pub mod message;
\ No newline at end of file
@@ -0,0 +1,30 @@
+use std::net::{IpAddr, Ipv4Addr};
+use serde::{Deserialize, Serialize};
+use schemars::JsonSchema;
+use validator::Validate;
+
+#[derive(Debug, Validate, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct KPAppApi {
+    #[serde(default = "default_api_address")]
+    pub address: IpAddr,
+    #[serde(default = "default_api_port")]
+    #[validate(range(min = 1, max = 65535))]
+    pub port: usize,
+    // requests must carry "Authorization: Bearer <token>" when set
+    #[serde(default)]
+    #[validate(length(min = 1))]
+    pub token: Option<String>,
+}
+
+fn default_api_address() -> IpAddr { IpAddr::V4(Ipv4Addr::LOCALHOST) }
+fn default_api_port() -> usize { 8000 }
+
+impl Default for KPAppApi {
+    fn default() -> Self {
+        KPAppApi {
+            address: default_api_address(),
+            port: default_api_port(),
+            token: None,
+        }
+    }
+}
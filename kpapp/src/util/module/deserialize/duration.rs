@@ -0,0 +1,87 @@
+use std::time::Duration;
+use serde::{Deserialize, Deserializer, Serializer};
+use serde_json::Value;
+use std::result::Result as StdResult;
+use schemars::gen::SchemaGenerator;
+use schemars::schema::Schema;
+use serde_json::json;
+use crate::util::module::deserialize::schema::json_schema;
+
+const DURATION_PATTERN: &str = r"^\s*\d+(\.\d+)?(\s*:\s*\d+(\.\d+)?)*\s*$";
+
+// accept seconds as number or "hh:mm:ss.ms" string
+pub fn parse_duration(value: &str) -> Option<Duration> {
+    let mut seconds = 0f64;
+    for part in value.trim().split(':') {
+        // digits only, f64 parsing would also take "nan", "inf" and exponents
+        let part = part.trim();
+        if part.is_empty() || !part.chars().all(|c| c.is_ascii_digit() || c == '.') {
+            return None;
+        }
+        let number = part.parse::<f64>().ok()?;
+        seconds = seconds * 60f64 + number;
+    }
+    seconds_to_duration(seconds)
+}
+
+// none for negative, non-finite or overflowing seconds
+pub fn seconds_to_duration(seconds: f64) -> Option<Duration> {
+    if !seconds.is_finite() || seconds < 0f64 {
+        return None;
+    }
+    Duration::try_from_secs_f64(seconds).ok()
+}
+
+pub fn option_duration<'de, D>(deserializer: D) -> StdResult<Option<Duration>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let value = Value::deserialize(deserializer)?;
+    match value {
+        Value::Null => Ok(None),
+        Value::String(s) => parse_duration(&s).map(Some).ok_or_else(|| serde::de::Error::custom(format!("invalid duration: {}", s))),
+        Value::Number(num) => match num.as_f64().and_then(seconds_to_duration) {
+            Some(duration) => Ok(Some(duration)),
+            None => Err(serde::de::Error::custom(format!("invalid duration: {}", num))),
+        },
+        _ => Err(serde::de::Error::custom("expected a duration in seconds or hh:mm:ss")),
+    }
+}
+
+pub fn duration<'de, D>(deserializer: D) -> StdResult<Duration, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    option_duration(deserializer)?.ok_or_else(|| serde::de::Error::custom("expected a duration in seconds or hh:mm:ss"))
+}
+
+pub fn serialize_duration<S>(duration: &Duration, serializer: S) -> StdResult<S::Ok, S::Error>
+where
+    S: Serializer,
+{
+    serializer.serialize_f64(duration.as_secs_f64())
+}
+
+pub fn serialize_option_duration<S>(duration: &Option<Duration>, serializer: S) -> StdResult<S::Ok, S::Error>
+where
+    S: Serializer,
+{
+    match duration {
+        None => serializer.serialize_none(),
+        Some(duration) => serializer.serialize_f64(duration.as_secs_f64()),
+    }
+}
+
+pub fn duration_schema(_: &mut SchemaGenerator) -> Schema {
+    json_schema(json!({
+        "description": "seconds or hh:mm:ss",
+        "anyOf": [{"type": "number", "minimum": 0}, {"type": "string", "pattern": DURATION_PATTERN}],
+    }))
+}
+
+pub fn option_duration_schema(_: &mut SchemaGenerator) -> Schema {
+    json_schema(json!({
+        "description": "seconds or hh:mm:ss",
+        "anyOf": [{"type": "number", "minimum": 0}, {"type": "string", "pattern": DURATION_PATTERN}, {"type": "null"}],
+    }))
+}
@@ -0,0 +1,97 @@
+use std::env;
+use std::fs;
+use std::result::Result as StdResult;
+use serde_json::Value;
+use kpserver::util::secret::register_secret;
+use crate::util::module::deserialize::locate::KPAppPathSegment;
+
+const FILE_PREFIX: &str = "@file:";
+const SECRET_PREFIX: &str = "secret:";
+
+// resolves ${VAR}, ${VAR:-default} and @file:/path in every string of the document,
+// "$${" keeps a literal "${". file contents and ${secret:VAR} values are registered as secrets and never logged
+pub fn interpolate(value: &mut Value) -> StdResult<(), (Vec<KPAppPathSegment>, String)> {
+    interpolate_value(value, &mut Vec::new())
+}
+
+fn interpolate_value(value: &mut Value, path: &mut Vec<KPAppPathSegment>) -> StdResult<(), (Vec<KPAppPathSegment>, String)> {
+    match value {
+        Value::String(s) => {
+            *s = interpolate_str(s).map_err(|err| (path.clone(), err))?;
+        }
+        Value::Array(list) => {
+            for (index, item) in list.iter_mut().enumerate() {
+                path.push(KPAppPathSegment::Index(index));
+                interpolate_value(item, path)?;
+                path.pop();
+            }
+        }
+        Value::Object(map) => {
+            for (key, item) in map.iter_mut() {
+                path.push(KPAppPathSegment::Key(key.clone()));
+                interpolate_value(item, path)?;
+                path.pop();
+            }
+        }
+        _ => {}
+    }
+    Ok(())
+}
+
+pub fn interpolate_str(value: &str) -> StdResult<String, String> {
+    // the whole value is read from the file, the trailing line break is dropped
+    if let Some(file_path) = value.strip_prefix(FILE_PREFIX) {
+        let content = fs::read_to_string(file_path.trim()).map_err(|err| format!("read secret file failed. path: {}, error: {}", file_path.trim(), err))?;
+        let secret = content.trim_end_matches(['\r', '\n']).to_string();
+        register_secret(&secret);
+        return Ok(secret);
+    }
+
+    let mut result = String::new();
+    let mut rest = value;
+    while let Some(start) = rest.find('$') {
+        result.push_str(&rest[..start]);
+        rest = &rest[start..];
+        if rest.starts_with("$${") {
+            result.push_str("${");
+            rest = &rest[3..];
+            continue;
+        }
+        if !rest.starts_with("${") {
+            result.push('$');
+            rest = &rest[1..];
+            continue;
+        }
+
+        let end = rest.find('}').ok_or_else(|| "unclosed variable reference".to_string())?;
+        let expression = &rest[2..end];
+        let (expression, secret) = match expression.strip_prefix(SECRET_PREFIX) {
+            Some(expression) => (expression, true),
+            None => (expression, false),
+        };
+        let (name, default) = match expression.split_once(":-") {
+            Some((name, default)) => (name, Some(default)),
+            None => (expression, None),
+        };
+        if name.is_empty() || !name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_') {
+            return Err(format!("invalid variable name. name: {}", name));
+        }
+
+        // like the shell, the default also replaces an empty variable
+        let resolved = match (env::var(name), default) {
+            (Ok(var), Some(default)) if var.is_empty() => default.to_string(),
+            (Ok(var), _) => {
+                if secret {
+                    register_secret(&var);
+                }
+                var
+            }
+            (Err(_), Some(default)) => default.to_string(),
+            (Err(_), None) => return Err(format!("environment variable not set. name: {}", name)),
+        };
+        result.push_str(&resolved);
+        rest = &rest[end + 1..];
+    }
+    result.push_str(rest);
+    Ok(result)
+}
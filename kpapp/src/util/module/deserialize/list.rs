@@ -0,0 +1,33 @@
+use serde::{Deserialize, Deserializer};
+use std::result::Result as StdResult;
+use schemars::gen::SchemaGenerator;
+use schemars::schema::Schema;
+use schemars::JsonSchema;
+use serde_json::json;
+use crate::util::module::deserialize::schema::json_schema;
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum OneOrMany<T> {
+    One(T),
+    Many(Vec<T>),
+}
+
+// accept a single object as a list of one
+pub fn one_or_many<'de, D, T>(deserializer: D) -> StdResult<Vec<T>, D::Error>
+where
+    D: Deserializer<'de>,
+    T: Deserialize<'de>,
+{
+    match OneOrMany::deserialize(deserializer)? {
+        OneOrMany::One(item) => Ok(vec![item]),
+        OneOrMany::Many(items) => Ok(items),
+    }
+}
+
+pub fn one_or_many_schema<T: JsonSchema>(gen: &mut SchemaGenerator) -> Schema {
+    let item = gen.subschema_for::<T>();
+    json_schema(json!({
+        "anyOf": [item, {"type": "array", "items": item}],
+    }))
+}
@@ -0,0 +1,144 @@
+use std::fmt::{Display, Formatter};
+use serde::de::{DeserializeSeed, Deserializer, Error, IgnoredAny, MapAccess, SeqAccess, Visitor};
+use std::result::Result as StdResult;
+
+const LOCATE_MARKER: &str = "kplayer locate marker";
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum KPAppPathSegment {
+    Key(String),
+    Index(usize),
+}
+
+impl Display for KPAppPathSegment {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            KPAppPathSegment::Key(key) => write!(f, ".{}", key),
+            KPAppPathSegment::Index(index) => write!(f, "[{}]", index),
+        }
+    }
+}
+
+pub fn format_path(path: &[KPAppPathSegment]) -> String {
+    let path: String = path.iter().map(|segment| segment.to_string()).collect();
+    path.trim_start_matches('.').to_string()
+}
+
+const WRAPPER_DEPTH: usize = 2;
+
+// walks the document along the path and fails on the target value,
+// so the format deserializer attaches its own line and column to the error.
+// enum wrappers such as {"Single": {"single": ...}} are skipped while searching a key
+pub struct KPAppLocateSeed<'a> {
+    path: &'a [KPAppPathSegment],
+    skip: usize,
+}
+
+impl<'a> KPAppLocateSeed<'a> {
+    pub fn new(path: &'a [KPAppPathSegment]) -> Self {
+        KPAppLocateSeed { path, skip: WRAPPER_DEPTH }
+    }
+}
+
+impl<'de, 'a> DeserializeSeed<'de> for KPAppLocateSeed<'a> {
+    type Value = ();
+
+    fn deserialize<D>(self, deserializer: D) -> StdResult<Self::Value, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_any(KPAppLocateVisitor { path: self.path, skip: self.skip })
+    }
+}
+
+struct KPAppLocateVisitor<'a> {
+    path: &'a [KPAppPathSegment],
+    skip: usize,
+}
+
+impl<'a> KPAppLocateVisitor<'a> {
+    fn scalar<E: Error>(&self) -> StdResult<(), E> {
+        match self.path.is_empty() {
+            true => Err(E::custom(LOCATE_MARKER)),
+            false => Ok(()),
+        }
+    }
+}
+
+impl<'de, 'a> Visitor<'de> for KPAppLocateVisitor<'a> {
+    type Value = ();
+
+    fn expecting(&self, formatter: &mut Formatter) -> std::fmt::Result {
+        formatter.write_str("any value")
+    }
+
+    fn visit_bool<E: Error>(self, _: bool) -> StdResult<(), E> { self.scalar() }
+    fn visit_i64<E: Error>(self, _: i64) -> StdResult<(), E> { self.scalar() }
+    fn visit_u64<E: Error>(self, _: u64) -> StdResult<(), E> { self.scalar() }
+    fn visit_f64<E: Error>(self, _: f64) -> StdResult<(), E> { self.scalar() }
+    fn visit_str<E: Error>(self, _: &str) -> StdResult<(), E> { self.scalar() }
+    fn visit_unit<E: Error>(self) -> StdResult<(), E> { self.scalar() }
+    fn visit_none<E: Error>(self) -> StdResult<(), E> { self.scalar() }
+
+    fn visit_some<D>(self, deserializer: D) -> StdResult<(), D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        KPAppLocateSeed { path: self.path, skip: self.skip }.deserialize(deserializer)
+    }
+
+    fn visit_seq<A>(self, mut seq: A) -> StdResult<(), A::Error>
+    where
+        A: SeqAccess<'de>,
+    {
+        let (target, rest) = match self.path.split_first() {
+            None => return Err(A::Error::custom(LOCATE_MARKER)),
+            Some((KPAppPathSegment::Index(index), rest)) => (*index, rest),
+            Some(_) => {
+                while seq.next_element::<IgnoredAny>()?.is_some() {}
+                return Ok(());
+            }
+        };
+        for _ in 0..target {
+            if seq.next_element::<IgnoredAny>()?.is_none() {
+                return Ok(());
+            }
+        }
+        // the nearest known position when the rest is not found
+        seq.next_element_seed(KPAppLocateSeed::new(rest))?;
+        Err(A::Error::custom(LOCATE_MARKER))
+    }
+
+    fn visit_map<A>(self, mut map: A) -> StdResult<(), A::Error>
+    where
+        A: MapAccess<'de>,
+    {
+        let (target, rest) = match self.path.split_first() {
+            None => return Err(A::Error::custom(LOCATE_MARKER)),
+            Some((KPAppPathSegment::Key(key), rest)) => (key, rest),
+            Some(_) => {
+                while map.next_entry::<IgnoredAny, IgnoredAny>()?.is_some() {}
+                return Ok(());
+            }
+        };
+        while let Some(key) = map.next_key::<String>()? {
+            if key.eq(target) {
+                map.next_value_seed(KPAppLocateSeed::new(rest))?;
+                return Err(A::Error::custom(LOCATE_MARKER));
+            }
+            match self.skip {
+                0 => { map.next_value::<IgnoredAny>()?; }
+                skip => map.next_value_seed(KPAppLocateSeed { path: self.path, skip: skip - 1 })?,
+            }
+        }
+        Ok(())
+    }
+}
+
+// convert a byte offset to 1-based line and column
+pub fn offset_to_location(content: &str, offset: usize) -> (usize, usize) {
+    let prefix = &content[..std::cmp::min(offset, content.len())];
+    let line = prefix.matches('\n').count() + 1;
+    let column = prefix.len() - prefix.rfind('\n').map_or(0, |index| index + 1) + 1;
+    (line, column)
+}
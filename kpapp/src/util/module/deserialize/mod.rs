@@ -1 +1,6 @@
-pub mod string;
\ No newline at end of file
+pub mod string;
+pub mod duration;
+pub mod list;
+pub mod locate;
+pub mod interpolate;
+pub mod schema;
\ No newline at end of file
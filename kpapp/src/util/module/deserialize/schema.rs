@@ -0,0 +1,7 @@
+use schemars::schema::Schema;
+use serde_json::Value;
+
+// the lenient deserializers accept more than their rust types, their schemas are written by hand
+pub fn json_schema(value: Value) -> Schema {
+    serde_json::from_value(value).unwrap_or(Schema::Bool(true))
+}
@@ -2,6 +2,10 @@ use std::collections::BTreeMap;
 use serde::{Deserialize, Deserializer};
 use serde_json::{Map, Value};
 use std::result::Result as StdResult;
+use schemars::gen::SchemaGenerator;
+use schemars::schema::Schema;
+use serde_json::json;
+use crate::util::module::deserialize::schema::json_schema;
 
 pub fn string_or_number<'de, D>(deserializer: D) -> StdResult<String, D::Error>
 where
@@ -34,4 +38,11 @@ where
         result.insert(key, value);
     }
     Ok(result)
-}
\ No newline at end of file
+}
+
+pub fn map_string_or_number_schema(_: &mut SchemaGenerator) -> Schema {
+    json_schema(json!({
+        "type": "object",
+        "additionalProperties": {"type": ["string", "number"]},
+    }))
+}
@@ -0,0 +1,148 @@
+use std::collections::BTreeMap;
+use std::str::FromStr;
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+use schemars::JsonSchema;
+use validator::Validate;
+use kpcodec::util::alias::{KPAVCodecId, KPAVMediaType, KPAVPixelFormat, KPAVRational, KPAVSampleFormat};
+use kpcodec::util::encode_parameter::{KPEncodeParameter, KPEncodeParameterPreset, KPEncodeParameterProfile};
+use crate::util::module::validator::encode::{validate_audio_encode, validate_video_encode};
+
+pub const ENCODE_OUTPUT_FORMAT: &str = "flv";
+
+#[derive(Debug, Validate, Clone, Serialize, Deserialize, JsonSchema)]
+#[validate(schema(function = "validate_video_encode"))]
+pub struct KPAppVideoEncode {
+    #[serde(default = "default_video_codec")]
+    pub codec: String,
+    #[serde(default = "default_video_width")]
+    #[validate(range(min = 16, max = 7680))]
+    pub width: usize,
+    #[serde(default = "default_video_height")]
+    #[validate(range(min = 16, max = 4320))]
+    pub height: usize,
+    #[serde(default = "default_video_framerate")]
+    #[validate(range(min = 1, max = 120))]
+    pub framerate: usize,
+    #[serde(default = "default_video_pix_fmt")]
+    pub pix_fmt: String,
+    #[serde(default)]
+    pub max_bitrate: usize,
+    #[serde(default)]
+    #[validate(range(max = 63))]
+    pub quality: u16,
+    #[serde(default = "default_video_profile")]
+    pub profile: String,
+    #[serde(default = "default_video_preset")]
+    pub preset: String,
+    #[serde(default = "default_video_gop")]
+    #[validate(range(min = 1, max = 60))]
+    pub gop: u16,
+}
+
+fn default_video_codec() -> String { "h264".to_string() }
+fn default_video_width() -> usize { 848 }
+fn default_video_height() -> usize { 480 }
+fn default_video_framerate() -> usize { 29 }
+fn default_video_pix_fmt() -> String { "yuv420p".to_string() }
+fn default_video_profile() -> String { "high".to_string() }
+fn default_video_preset() -> String { "veryfast".to_string() }
+fn default_video_gop() -> u16 { 2 }
+
+impl Default for KPAppVideoEncode {
+    fn default() -> Self {
+        KPAppVideoEncode {
+            codec: default_video_codec(),
+            width: default_video_width(),
+            height: default_video_height(),
+            framerate: default_video_framerate(),
+            pix_fmt: default_video_pix_fmt(),
+            max_bitrate: 0,
+            quality: 0,
+            profile: default_video_profile(),
+            preset: default_video_preset(),
+            gop: default_video_gop(),
+        }
+    }
+}
+
+impl KPAppVideoEncode {
+    pub fn to_encode_parameter(&self) -> Result<KPEncodeParameter> {
+        Ok(KPEncodeParameter::Video {
+            codec_id: KPAVCodecId::from_name(&self.codec)?,
+            width: self.width,
+            height: self.height,
+            pix_fmt: KPAVPixelFormat::from_name(&self.pix_fmt)?,
+            framerate: KPAVRational::from_fps(self.framerate),
+            max_bitrate: self.max_bitrate,
+            quality: self.quality,
+            profile: KPEncodeParameterProfile::from_str(&self.profile).map_err(|err| anyhow!("unknown encode profile. profile: {}, error: {}", self.profile, err))?,
+            preset: KPEncodeParameterPreset::from_str(&self.preset).map_err(|err| anyhow!("unknown encode preset. preset: {}, error: {}", self.preset, err))?,
+            gop_uint: self.gop,
+            metadata: BTreeMap::new(),
+        })
+    }
+}
+
+#[derive(Debug, Validate, Clone, Serialize, Deserialize, JsonSchema)]
+#[validate(schema(function = "validate_audio_encode"))]
+pub struct KPAppAudioEncode {
+    #[serde(default = "default_audio_codec")]
+    pub codec: String,
+    #[serde(default = "default_audio_sample_rate")]
+    #[validate(range(min = 8000, max = 192000))]
+    pub sample_rate: usize,
+    #[serde(default = "default_audio_sample_fmt")]
+    pub sample_fmt: String,
+    #[serde(default = "default_audio_channels")]
+    #[validate(range(min = 1, max = 8))]
+    pub channels: usize,
+}
+
+fn default_audio_codec() -> String { "aac".to_string() }
+fn default_audio_sample_rate() -> usize { 48000 }
+fn default_audio_sample_fmt() -> String { "fltp".to_string() }
+fn default_audio_channels() -> usize { 2 }
+
+impl Default for KPAppAudioEncode {
+    fn default() -> Self {
+        KPAppAudioEncode {
+            codec: default_audio_codec(),
+            sample_rate: default_audio_sample_rate(),
+            sample_fmt: default_audio_sample_fmt(),
+            channels: default_audio_channels(),
+        }
+    }
+}
+
+impl KPAppAudioEncode {
+    pub fn to_encode_parameter(&self) -> Result<KPEncodeParameter> {
+        Ok(KPEncodeParameter::Audio {
+            codec_id: KPAVCodecId::from_name(&self.codec)?,
+            sample_rate: self.sample_rate,
+            sample_fmt: KPAVSampleFormat::from_name(&self.sample_fmt)?,
+            channel_layout: KPEncodeParameter::get_channel_layout(self.channels),
+            channels: self.channels,
+            metadata: BTreeMap::new(),
+        })
+    }
+}
+
+#[derive(Debug, Validate, Clone, Serialize, Deserialize, Default, JsonSchema)]
+pub struct KPAppEncode {
+    #[serde(default)]
+    #[validate(nested)]
+    pub video: KPAppVideoEncode,
+    #[serde(default)]
+    #[validate(nested)]
+    pub audio: KPAppAudioEncode,
+}
+
+impl KPAppEncode {
+    pub fn to_encode_parameter(&self) -> Result<BTreeMap<KPAVMediaType, KPEncodeParameter>> {
+        let mut encode_parameter = BTreeMap::new();
+        encode_parameter.insert(KPAVMediaType::KPAVMEDIA_TYPE_VIDEO, self.video.to_encode_parameter()?);
+        encode_parameter.insert(KPAVMediaType::KPAVMEDIA_TYPE_AUDIO, self.audio.to_encode_parameter()?);
+        Ok(encode_parameter)
+    }
+}
@@ -0,0 +1,40 @@
+use std::time::Duration;
+use serde::{Deserialize, Serialize};
+use schemars::JsonSchema;
+use validator::Validate;
+use crate::util::module::deserialize::duration::{duration, serialize_duration, duration_schema};
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default, Eq, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum KPAppFailurePolicy {
+    #[default]
+    Skip,
+    Retry,
+    Abort,
+}
+
+// retry falls back to skip once retry_count attempts have failed
+#[derive(Debug, Validate, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct KPAppFailure {
+    #[serde(default)]
+    pub policy: KPAppFailurePolicy,
+    #[serde(default = "default_failure_retry_count")]
+    #[validate(range(min = 1, max = 100))]
+    pub retry_count: usize,
+    #[serde(default = "default_failure_retry_interval", deserialize_with = "duration", serialize_with = "serialize_duration")]
+    #[schemars(schema_with = "duration_schema")]
+    pub retry_interval: Duration,
+}
+
+fn default_failure_retry_count() -> usize { 3 }
+fn default_failure_retry_interval() -> Duration { Duration::from_secs(1) }
+
+impl Default for KPAppFailure {
+    fn default() -> Self {
+        KPAppFailure {
+            policy: Default::default(),
+            retry_count: default_failure_retry_count(),
+            retry_interval: default_failure_retry_interval(),
+        }
+    }
+}
@@ -0,0 +1,47 @@
+use std::path::Path;
+use serde::{Deserialize, Serialize};
+use schemars::JsonSchema;
+use validator::Validate;
+use crate::util::module::encode::KPAppEncode;
+use crate::util::module::scene::KPAppSceneOverride;
+use crate::util::module::validator::file::*;
+
+pub const FALLBACK_INPUT_FORMAT: &str = "lavfi";
+
+// slate kept on air while the playlist has nothing playable
+#[derive(Debug, Validate, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct KPAppFallback {
+    #[validate(custom(function = "exist_file"))]
+    #[validate(custom(function = "slate_extension"))]
+    pub path: String,
+    #[serde(default)]
+    #[validate(nested)]
+    pub scene: Option<KPAppSceneOverride>,
+}
+
+impl KPAppFallback {
+    // loop the image or clip forever, the clip's own audio is replaced with silence
+    pub fn to_filter_graph(&self, encode: &KPAppEncode) -> String {
+        let movie = format!("movie={}", quote_filter_argument(&quote_filter_argument(&self.path)));
+        let video = match is_image(&self.path) {
+            true => format!("{},loop=loop=-1:size=1,setpts=N/{}/TB", movie, encode.video.framerate),
+            false => format!("{}:loop=0,setpts=N/FRAME_RATE/TB", movie),
+        };
+        let channel_layout = match encode.audio.channels {
+            1 => "mono".to_string(),
+            2 => "stereo".to_string(),
+            channels => format!("{}c", channels),
+        };
+        format!("{}[out0];anullsrc=r={}:cl={}[out1]", video, encode.audio.sample_rate, channel_layout)
+    }
+}
+
+fn is_image(path: &str) -> bool {
+    let extension = Path::new(path).extension().and_then(|e| e.to_str()).map(|e| e.to_lowercase());
+    extension.map_or(false, |extension| IMAGE_EXTENSIONS.contains(&extension.as_str()))
+}
+
+// one level of filtergraph quoting, the graph parser and the option parser each remove one
+fn quote_filter_argument(value: &str) -> String {
+    format!("'{}'", value.replace('\'', "'\\''"))
+}
@@ -0,0 +1,25 @@
+use std::net::{IpAddr, Ipv4Addr};
+use serde::{Deserialize, Serialize};
+use schemars::JsonSchema;
+use validator::Validate;
+
+#[derive(Debug, Validate, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct KPAppMetrics {
+    #[serde(default = "default_metrics_address")]
+    pub address: IpAddr,
+    #[serde(default = "default_metrics_port")]
+    #[validate(range(min = 1, max = 65535))]
+    pub port: usize,
+}
+
+fn default_metrics_address() -> IpAddr { IpAddr::V4(Ipv4Addr::LOCALHOST) }
+fn default_metrics_port() -> usize { 9100 }
+
+impl Default for KPAppMetrics {
+    fn default() -> Self {
+        KPAppMetrics {
+            address: default_metrics_address(),
+            port: default_metrics_port(),
+        }
+    }
+}
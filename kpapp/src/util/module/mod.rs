@@ -1,5 +1,13 @@
 pub(crate) mod resource;
 pub(crate) mod scene;
 pub(crate) mod output;
-mod validator;
-mod deserialize;
\ No newline at end of file
+pub(crate) mod schedule;
+pub(crate) mod encode;
+pub(crate) mod failure;
+pub(crate) mod server;
+pub(crate) mod api;
+pub(crate) mod metrics;
+pub(crate) mod shutdown;
+pub(crate) mod fallback;
+pub(crate) mod validator;
+pub(crate) mod deserialize;
\ No newline at end of file
@@ -1,11 +1,34 @@
+use std::time::Duration;
 use serde::{Deserialize, Serialize};
+use schemars::JsonSchema;
 use crate::util::module::validator::protocol::*;
 use validator::Validate;
-use crate::util::module::resource::KPAppResourceItem;
+use crate::util::module::deserialize::duration::{option_duration, serialize_option_duration, option_duration_schema};
 
-#[derive(Debug, Validate, Clone, Serialize, Deserialize)]
+#[derive(Debug, Validate, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct KPAppOutput {
     pub name: String,
     #[validate(custom(function = "rtmp_or_file_url"))]
+    #[schemars(regex(pattern = r"^(rtmp|file)://"))]
     pub path: String,
-}
\ No newline at end of file
+    #[serde(default = "default_output_timeout", deserialize_with = "option_duration", serialize_with = "serialize_option_duration")]
+    #[schemars(schema_with = "option_duration_schema")]
+    pub timeout: Option<Duration>,
+    #[serde(default = "default_output_retry_interval", deserialize_with = "option_duration", serialize_with = "serialize_option_duration")]
+    #[schemars(schema_with = "option_duration_schema")]
+    pub retry_interval: Option<Duration>,
+}
+
+fn default_output_timeout() -> Option<Duration> { Some(Duration::from_secs(10)) }
+fn default_output_retry_interval() -> Option<Duration> { Some(Duration::from_secs(5)) }
+
+impl KPAppOutput {
+    pub fn new<T: ToString>(name: T, path: T) -> Self {
+        KPAppOutput {
+            name: name.to_string(),
+            path: path.to_string(),
+            timeout: default_output_timeout(),
+            retry_interval: default_output_retry_interval(),
+        }
+    }
+}
@@ -1,19 +1,311 @@
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use glob::Pattern;
 use log::info;
+use rand::seq::SliceRandom;
 use serde::{Deserialize, Deserializer, Serialize};
+use schemars::JsonSchema;
 use serde::de::Error;
-use serde_json::Value;
+use serde_json::{json, Value};
+use schemars::gen::SchemaGenerator;
+use schemars::schema::Schema;
 use crate::util::module::validator::file::*;
-use validator::Validate;
+use validator::{Validate, ValidationErrors};
 use kpcodec::util::alias::KPAVMediaType;
-use crate::util::common::generate_unique_string;
-use super::validator::resource::validate_unique_names;
+use super::validator::resource::{validate_trim, validate_unique_names};
+use crate::util::module::deserialize::duration::{duration, option_duration, serialize_duration, serialize_option_duration, duration_schema, option_duration_schema, seconds_to_duration};
+use crate::util::module::validator::protocol::network_url;
+use crate::util::module::deserialize::schema::json_schema;
+use crate::util::module::scene::KPAppSceneOverride;
 
-#[derive(Debug, Validate, Clone, Serialize, Deserialize)]
+#[derive(Debug, Validate, Clone, Serialize, Deserialize, Default, JsonSchema)]
+#[validate(schema(function = "validate_trim"))]
 pub struct SingleDetail {
     #[validate(custom(function = "exist_file"))]
     #[validate(custom(function = "video_extension"))]
     pub path: String,
+    #[schemars(with = "HashMap<String, Option<usize>>")]
+    pub expect_streams: HashMap<KPAVMediaType, Option<usize>>,
+    #[serde(default, deserialize_with = "option_duration", serialize_with = "serialize_option_duration")]
+    #[schemars(schema_with = "option_duration_schema")]
+    pub start: Option<Duration>,
+    #[serde(default, deserialize_with = "option_duration", serialize_with = "serialize_option_duration")]
+    #[schemars(schema_with = "option_duration_schema")]
+    pub end: Option<Duration>,
+    #[serde(default, deserialize_with = "option_duration", serialize_with = "serialize_option_duration")]
+    #[schemars(schema_with = "option_duration_schema")]
+    pub duration: Option<Duration>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub metadata: BTreeMap<String, String>,
+}
+
+impl SingleDetail {
+    // none when start plus duration overflows, validate_trim rejects that
+    pub fn get_end_point(&self) -> Option<Duration> {
+        match (self.end, self.duration) {
+            (Some(end), _) => Some(end),
+            (None, Some(duration)) => self.start.unwrap_or_default().checked_add(duration),
+            (None, None) => None,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default, Eq, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum DirectorySort {
+    #[default]
+    Name,
+    Mtime,
+    Random,
+}
+
+#[derive(Debug, Validate, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct DirectoryDetail {
+    #[validate(custom(function = "exist_directory"))]
+    pub path: String,
+    #[serde(default)]
+    #[validate(custom(function = "glob_patterns"))]
+    pub include: Vec<String>,
+    #[serde(default)]
+    #[validate(custom(function = "glob_patterns"))]
+    pub exclude: Vec<String>,
+    #[serde(default)]
+    pub recursive: bool,
+    #[serde(default)]
+    pub sort: DirectorySort,
+    #[serde(default)]
+    #[schemars(with = "HashMap<String, Option<usize>>")]
+    pub expect_streams: HashMap<KPAVMediaType, Option<usize>>,
+}
+
+impl DirectoryDetail {
+    pub fn scan(&self) -> Result<Vec<PathBuf>, std::io::Error> {
+        let root = PathBuf::from(&self.path);
+        let include: Vec<Pattern> = self.include.iter().filter_map(|p| Pattern::new(p).ok()).collect();
+        let exclude: Vec<Pattern> = self.exclude.iter().filter_map(|p| Pattern::new(p).ok()).collect();
+
+        let mut files = Vec::new();
+        let mut dirs = vec![root.clone()];
+        while let Some(dir) = dirs.pop() {
+            for entry in fs::read_dir(&dir)? {
+                let path = entry?.path();
+                if path.is_dir() {
+                    if self.recursive { dirs.push(path); }
+                    continue;
+                }
+
+                // match relative path
+                let relative_path = path.strip_prefix(&root).unwrap_or(&path).to_path_buf();
+                let included = match include.is_empty() {
+                    true => video_extension(&path.to_string_lossy()).is_ok(),
+                    false => include.iter().any(|pattern| pattern.matches_path(&relative_path)),
+                };
+                if !included || exclude.iter().any(|pattern| pattern.matches_path(&relative_path)) {
+                    continue;
+                }
+                files.push(path);
+            }
+        }
+
+        // sort
+        match self.sort {
+            DirectorySort::Name => files.sort(),
+            DirectorySort::Mtime => files.sort_by_key(|path| (fs::metadata(path).and_then(|m| m.modified()).ok(), path.clone())),
+            DirectorySort::Random => files.shuffle(&mut rand::thread_rng()),
+        }
+        Ok(files)
+    }
+}
+
+#[derive(Debug, Validate, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct PlaylistDetail {
+    #[validate(custom(function = "exist_file"))]
+    #[validate(custom(function = "playlist_extension"))]
+    pub path: String,
+    #[serde(default)]
+    #[schemars(with = "HashMap<String, Option<usize>>")]
+    pub expect_streams: HashMap<KPAVMediaType, Option<usize>>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct PlaylistEntry {
+    pub path: String,
+    pub title: Option<String>,
+    pub duration: Option<Duration>,
+}
+
+impl PlaylistDetail {
+    // m3u, extended m3u and pls, relative paths resolve against the playlist file
+    pub fn read(&self) -> Result<Vec<PlaylistEntry>, std::io::Error> {
+        let path = Path::new(&self.path);
+        let content = fs::read(path)?;
+        let content = String::from_utf8_lossy(&content);
+        let content = content.trim_start_matches('\u{feff}');
+
+        let mut entries = match path.extension().and_then(|e| e.to_str()).map(|e| e.to_lowercase()) {
+            Some(extension) if extension == "pls" => parse_pls(content),
+            _ => parse_m3u(content),
+        };
+        let base = path.parent().unwrap_or(Path::new(""));
+        for entry in entries.iter_mut() {
+            entry.path = resolve_playlist_path(base, &entry.path);
+        }
+        Ok(entries)
+    }
+}
+
+fn parse_m3u(content: &str) -> Vec<PlaylistEntry> {
+    let mut entries = Vec::new();
+    let mut info: Option<(Option<Duration>, Option<String>)> = None;
+    for line in content.lines().map(|line| line.trim()).filter(|line| !line.is_empty()) {
+        if let Some(extinf) = line.strip_prefix("#EXTINF:") {
+            // #EXTINF:<duration> [attributes],<title>
+            let (head, title) = extinf.split_once(',').unwrap_or((extinf, ""));
+            let duration = head.split_whitespace().next().and_then(parse_playlist_duration);
+            let title = Some(title.trim().to_string()).filter(|title| !title.is_empty());
+            info = Some((duration, title));
+            continue;
+        }
+        if line.starts_with('#') {
+            continue;
+        }
+
+        let (duration, title) = info.take().unwrap_or_default();
+        entries.push(PlaylistEntry { path: line.to_string(), title, duration });
+    }
+    entries
+}
+
+fn parse_pls(content: &str) -> Vec<PlaylistEntry> {
+    let mut entries: BTreeMap<usize, PlaylistEntry> = BTreeMap::new();
+    for line in content.lines().map(|line| line.trim()) {
+        let (key, value) = match line.split_once('=') {
+            Some((key, value)) => (key.trim().to_lowercase(), value.trim()),
+            None => continue,
+        };
+        let (field, index) = match key.find(|c: char| c.is_ascii_digit()) {
+            Some(position) => (&key[..position], key[position..].parse::<usize>()),
+            None => continue,
+        };
+        let index = match index {
+            Ok(index) => index,
+            Err(_) => continue,
+        };
+
+        let entry = entries.entry(index).or_default();
+        match field {
+            "file" => entry.path = value.to_string(),
+            "title" if !value.is_empty() => entry.title = Some(value.to_string()),
+            "length" => entry.duration = parse_playlist_duration(value),
+            _ => {}
+        }
+    }
+    entries.into_values().filter(|entry| !entry.path.is_empty()).collect()
+}
+
+// negative length means unknown, e.g. a live stream
+fn parse_playlist_duration(value: &str) -> Option<Duration> {
+    value.parse::<f64>().ok().and_then(seconds_to_duration)
+}
+
+fn resolve_playlist_path(base: &Path, path: &str) -> String {
+    if path.contains("://") && !path.starts_with("file://") {
+        return path.to_string();
+    }
+    let path = Path::new(path.trim_start_matches("file://"));
+    match path.is_absolute() {
+        true => path.to_string_lossy().to_string(),
+        false => base.join(path).to_string_lossy().to_string(),
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default, Eq, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum MixEndWith {
+    #[default]
+    Longest,
+    Video,
+    Audio,
+}
+
+#[derive(Debug, Validate, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct MixDetail {
+    #[validate(custom(function = "exist_file"))]
+    #[validate(custom(function = "video_extension"))]
+    pub video: String,
+    #[validate(custom(function = "exist_file"))]
+    #[validate(custom(function = "audio_extension"))]
+    pub audio: String,
+    #[serde(default)]
+    pub end_with: MixEndWith,
+    #[serde(default)]
+    #[schemars(with = "HashMap<String, Option<usize>>")]
+    pub expect_streams: HashMap<KPAVMediaType, Option<usize>>,
+}
+
+#[derive(Debug, Validate, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct NetworkReconnect {
+    #[serde(default = "default_reconnect_max_retries")]
+    pub max_retries: usize,
+    #[serde(default = "default_reconnect_interval", deserialize_with = "duration", serialize_with = "serialize_duration")]
+    #[schemars(schema_with = "duration_schema")]
+    pub interval: Duration,
+    #[serde(default = "default_reconnect_max_interval", deserialize_with = "duration", serialize_with = "serialize_duration")]
+    #[schemars(schema_with = "duration_schema")]
+    pub max_interval: Duration,
+    #[serde(default = "default_reconnect_stable_window", deserialize_with = "duration", serialize_with = "serialize_duration")]
+    #[schemars(schema_with = "duration_schema")]
+    pub stable_window: Duration,
+}
+
+impl Default for NetworkReconnect {
+    fn default() -> Self {
+        NetworkReconnect {
+            max_retries: default_reconnect_max_retries(),
+            interval: default_reconnect_interval(),
+            max_interval: default_reconnect_max_interval(),
+            stable_window: default_reconnect_stable_window(),
+        }
+    }
+}
+
+impl NetworkReconnect {
+    // exponential backoff capped by max_interval
+    pub fn get_interval(&self, attempt: usize) -> Duration {
+        let factor = 2u32.saturating_pow(attempt.min(16) as u32);
+        std::cmp::min(self.interval.saturating_mul(factor), self.max_interval)
+    }
+
+    // attempt count after an input that was up for uptime dropped
+    pub fn next_attempt(&self, attempt: usize, uptime: Duration) -> usize {
+        if uptime >= self.stable_window { 0 } else { attempt }
+    }
+}
+
+fn default_reconnect_max_retries() -> usize { 5 }
+fn default_reconnect_interval() -> Duration { Duration::from_secs(1) }
+fn default_reconnect_max_interval() -> Duration { Duration::from_secs(30) }
+fn default_reconnect_stable_window() -> Duration { Duration::from_secs(60) }
+fn default_network_timeout() -> Duration { Duration::from_secs(10) }
+
+#[derive(Debug, Validate, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct NetworkDetail {
+    #[validate(custom(function = "network_url"))]
+    #[schemars(regex(pattern = r"^(rtmps?|https?|srt)://.+"))]
+    pub url: String,
+    #[serde(default = "default_network_timeout", deserialize_with = "duration", serialize_with = "serialize_duration")]
+    #[schemars(schema_with = "duration_schema")]
+    pub open_timeout: Duration,
+    #[serde(default = "default_network_timeout", deserialize_with = "duration", serialize_with = "serialize_duration")]
+    #[schemars(schema_with = "duration_schema")]
+    pub read_timeout: Duration,
+    #[serde(default)]
+    #[validate(nested)]
+    pub reconnect: NetworkReconnect,
+    #[serde(default)]
+    #[schemars(with = "HashMap<String, Option<usize>>")]
     pub expect_streams: HashMap<KPAVMediaType, Option<usize>>,
 }
 
@@ -22,6 +314,36 @@ pub enum ResourceItem {
     Single {
         single: SingleDetail
     },
+    Directory {
+        directory: DirectoryDetail
+    },
+    Mix {
+        mix: MixDetail
+    },
+    Network {
+        network: NetworkDetail
+    },
+    Playlist {
+        playlist: PlaylistDetail
+    },
+}
+
+impl Validate for ResourceItem {
+    fn validate(&self) -> Result<(), ValidationErrors> {
+        match self {
+            // a url is opened by the decoder, only a local file is checked on disk
+            ResourceItem::Single { single } if single.path.contains("://") => validate_trim(single).map_err(|err| {
+                let mut errors = ValidationErrors::new();
+                errors.add("__all__", err);
+                errors
+            }),
+            ResourceItem::Single { single } => single.validate(),
+            ResourceItem::Directory { directory } => directory.validate(),
+            ResourceItem::Mix { mix } => mix.validate(),
+            ResourceItem::Network { network } => network.validate(),
+            ResourceItem::Playlist { playlist } => playlist.validate(),
+        }
+    }
 }
 
 
@@ -36,12 +358,52 @@ impl<'de> Deserialize<'de> for ResourceItem {
                 Ok(ResourceItem::Single {
                     single: SingleDetail {
                         path,
-                        expect_streams: Default::default(),
+                        ..Default::default()
                     },
                 })
             }
             Value::Object(map) => {
-                let single_value = map.get("Single").ok_or_else(|| D::Error::custom("Missing 'Single' in ResourceItem object"))?;
+                if let Some(directory_value) = map.get("Directory") {
+                    let directory_map = match directory_value {
+                        Value::Object(map) => map,
+                        _ => return Err(D::Error::custom("Invalid type within 'Directory' object")),
+                    };
+                    let directory_map_detail = directory_map.get("directory").ok_or_else(|| D::Error::custom("Missing 'directory' object within 'Directory'"))?;
+                    let directory: DirectoryDetail = serde_json::from_value(directory_map_detail.clone()).map_err(D::Error::custom)?;
+                    return Ok(ResourceItem::Directory { directory });
+                }
+
+                if let Some(mix_value) = map.get("Mix") {
+                    let mix_map = match mix_value {
+                        Value::Object(map) => map,
+                        _ => return Err(D::Error::custom("Invalid type within 'Mix' object")),
+                    };
+                    let mix_map_detail = mix_map.get("mix").ok_or_else(|| D::Error::custom("Missing 'mix' object within 'Mix'"))?;
+                    let mix: MixDetail = serde_json::from_value(mix_map_detail.clone()).map_err(D::Error::custom)?;
+                    return Ok(ResourceItem::Mix { mix });
+                }
+
+                if let Some(network_value) = map.get("Network") {
+                    let network_map = match network_value {
+                        Value::Object(map) => map,
+                        _ => return Err(D::Error::custom("Invalid type within 'Network' object")),
+                    };
+                    let network_map_detail = network_map.get("network").ok_or_else(|| D::Error::custom("Missing 'network' object within 'Network'"))?;
+                    let network: NetworkDetail = serde_json::from_value(network_map_detail.clone()).map_err(D::Error::custom)?;
+                    return Ok(ResourceItem::Network { network });
+                }
+
+                if let Some(playlist_value) = map.get("Playlist") {
+                    let playlist_map = match playlist_value {
+                        Value::Object(map) => map,
+                        _ => return Err(D::Error::custom("Invalid type within 'Playlist' object")),
+                    };
+                    let playlist_map_detail = playlist_map.get("playlist").ok_or_else(|| D::Error::custom("Missing 'playlist' object within 'Playlist'"))?;
+                    let playlist: PlaylistDetail = serde_json::from_value(playlist_map_detail.clone()).map_err(D::Error::custom)?;
+                    return Ok(ResourceItem::Playlist { playlist });
+                }
+
+                let single_value = map.get("Single").ok_or_else(|| D::Error::custom("Missing 'Single', 'Directory', 'Mix', 'Network' or 'Playlist' in ResourceItem object"))?;
                 let single_map = match single_value {
                     Value::Object(map) => map,
                     _ => return Err(D::Error::custom("Invalid type within 'Single' object")),
@@ -56,10 +418,39 @@ impl<'de> Deserialize<'de> for ResourceItem {
 }
 
 
-#[derive(Serialize, Clone, Debug)]
+// a bare string is the path of a single item, objects keep the {"Single": {"single": {...}}} wrapper
+impl JsonSchema for ResourceItem {
+    fn schema_name() -> String {
+        "ResourceItem".to_string()
+    }
+
+    fn json_schema(gen: &mut SchemaGenerator) -> Schema {
+        let wrapper = |variant: &str, field: &str, detail: Schema| json!({
+            "type": "object",
+            "required": [variant],
+            "properties": {variant: {"type": "object", "required": [field], "properties": {field: detail}}},
+        });
+        json_schema(json!({
+            "anyOf": [
+                {"type": "string"},
+                wrapper("Single", "single", gen.subschema_for::<SingleDetail>()),
+                wrapper("Directory", "directory", gen.subschema_for::<DirectoryDetail>()),
+                wrapper("Mix", "mix", gen.subschema_for::<MixDetail>()),
+                wrapper("Network", "network", gen.subschema_for::<NetworkDetail>()),
+                wrapper("Playlist", "playlist", gen.subschema_for::<PlaylistDetail>()),
+            ],
+        }))
+    }
+}
+
+#[derive(Serialize, Clone, Debug, Validate)]
 pub struct KPAppResourceItem {
     pub name: String,
+    #[validate(nested)]
     pub resource: ResourceItem,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[validate(nested)]
+    pub scene: Option<KPAppSceneOverride>,
 }
 
 impl<'de> Deserialize<'de> for KPAppResourceItem {
@@ -69,15 +460,17 @@ impl<'de> Deserialize<'de> for KPAppResourceItem {
     {
         let value: Value = Deserialize::deserialize(deserializer)?;
         match value {
+            // named after the path so the name stays the same across reloads and restarts
             Value::String(path) => {
                 Ok(KPAppResourceItem {
-                    name: generate_unique_string(),
+                    name: path.clone(),
                     resource: ResourceItem::Single {
                         single: SingleDetail {
                             path,
-                            expect_streams: Default::default(),
+                            ..Default::default()
                         },
                     },
+                    scene: None,
                 })
             }
             Value::Object(map) => {
@@ -86,11 +479,16 @@ impl<'de> Deserialize<'de> for KPAppResourceItem {
                 // extract and deserialize the resource field
                 let resource_value = map.get("resource").ok_or_else(|| D::Error::custom("Missing field 'resource'"))?;
                 let resource: ResourceItem = serde_json::from_value(resource_value.clone()).map_err(D::Error::custom)?;
+                let scene = match map.get("scene") {
+                    Some(scene_value) => Some(serde_json::from_value(scene_value.clone()).map_err(D::Error::custom)?),
+                    None => None,
+                };
 
                 // Construct and return the KPAppResourceItem
                 Ok(KPAppResourceItem {
                     name,
                     resource,
+                    scene,
                 })
             }
             _ => Err(D::Error::custom("Invalid type for ResourceItem")),
@@ -98,9 +496,68 @@ impl<'de> Deserialize<'de> for KPAppResourceItem {
     }
 }
 
-#[derive(Serialize, Deserialize, Clone, Debug, Validate)]
+// a bare string is a single item named by a generated id
+impl JsonSchema for KPAppResourceItem {
+    fn schema_name() -> String {
+        "KPAppResourceItem".to_string()
+    }
+
+    fn json_schema(gen: &mut SchemaGenerator) -> Schema {
+        json_schema(json!({
+            "anyOf": [
+                {"type": "string"},
+                {
+                    "type": "object",
+                    "required": ["name", "resource"],
+                    "properties": {
+                        "name": {"type": "string"},
+                        "resource": gen.subschema_for::<ResourceItem>(),
+                        "scene": gen.subschema_for::<KPAppSceneOverride>(),
+                    },
+                },
+            ],
+        }))
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default, Eq, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum KPAppResourceMode {
+    #[default]
+    Sequence,
+    RepeatAll,
+    RepeatOne,
+    Shuffle,
+    ShuffleNoRepeat,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Validate, JsonSchema)]
 pub struct KPAppResource {
     pub name: String,
+    #[serde(default)]
+    pub mode: KPAppResourceMode,
+    #[serde(deserialize_with = "resource_list")]
     #[validate(custom(function = "validate_unique_names"))]
+    #[validate(nested)]
     pub list: Vec<KPAppResourceItem>,
+}
+
+// a bare string item repeating an earlier path is named after its position as well
+pub fn resource_list<'de, D>(deserializer: D) -> Result<Vec<KPAppResourceItem>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let values: Vec<Value> = Deserialize::deserialize(deserializer)?;
+    let mut paths = HashSet::new();
+    values.into_iter().enumerate().map(|(index, value)| {
+        let repeated = match &value {
+            Value::String(path) => !paths.insert(path.clone()),
+            _ => false,
+        };
+        let mut item: KPAppResourceItem = serde_json::from_value(value).map_err(D::Error::custom)?;
+        if repeated {
+            item.name = format!("{}#{}", item.name, index + 1);
+        }
+        Ok(item)
+    }).collect()
 }
\ No newline at end of file
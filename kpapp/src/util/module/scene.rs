@@ -1,19 +1,58 @@
 use std::collections::BTreeMap;
+use log::warn;
 use serde::{Deserialize, Serialize};
+use schemars::JsonSchema;
 use crate::util::module::validator::protocol::*;
 use validator::Validate;
-use crate::util::module::deserialize::string::map_string_or_number;
+use crate::util::module::deserialize::string::{map_string_or_number, map_string_or_number_schema};
 
-#[derive(Debug, Clone, Serialize, Deserialize, Validate)]
+#[derive(Debug, Clone, Serialize, Deserialize, Validate, JsonSchema)]
 pub struct KPAppPlugin {
     pub name: String,
     #[serde(deserialize_with = "map_string_or_number")]
+    #[schemars(schema_with = "map_string_or_number_schema")]
     pub arguments: BTreeMap<String, String>,
 }
 
-#[derive(Debug, Validate, Clone, Serialize, Deserialize)]
+#[derive(Debug, Validate, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct KPAppScene {
     pub name: String,
     #[validate(nested)]
     pub list: Vec<KPAppPlugin>,
-}
\ No newline at end of file
+}
+
+// per item changes on top of the global scene
+#[derive(Debug, Validate, Clone, Serialize, Deserialize, Default, JsonSchema)]
+pub struct KPAppSceneOverride {
+    #[serde(default)]
+    pub remove: Vec<String>,
+    #[serde(default)]
+    #[validate(nested)]
+    pub arguments: Vec<KPAppPlugin>,
+    #[serde(default)]
+    #[validate(nested)]
+    pub add: Vec<KPAppPlugin>,
+}
+
+impl KPAppScene {
+    // removals first, then argument overrides, additions run after the global plugins
+    pub fn merge(&self, scene_override: &KPAppSceneOverride) -> KPAppScene {
+        let mut scene = self.clone();
+        scene.list.retain(|plugin| !scene_override.remove.contains(&plugin.name));
+
+        for override_plugin in scene_override.arguments.iter() {
+            match scene.list.iter_mut().find(|plugin| plugin.name == override_plugin.name) {
+                Some(plugin) => plugin.arguments.extend(override_plugin.arguments.clone()),
+                None => warn!("scene override plugin not found. name: {}", override_plugin.name),
+            }
+        }
+
+        for plugin in scene_override.add.iter() {
+            match scene.list.iter_mut().find(|exist| exist.name == plugin.name) {
+                Some(exist) => exist.arguments.extend(plugin.arguments.clone()),
+                None => scene.list.push(plugin.clone()),
+            }
+        }
+        scene
+    }
+}
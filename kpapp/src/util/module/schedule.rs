@@ -0,0 +1,54 @@
+use serde::{Deserialize, Serialize};
+use schemars::JsonSchema;
+use validator::Validate;
+use crate::util::module::resource::{resource_list, KPAppResource, KPAppResourceItem, KPAppResourceMode};
+use crate::util::module::validator::resource::validate_unique_names;
+use crate::util::module::validator::schedule::*;
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default, Eq, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum KPAppSchedulePolicy {
+    HardCut,
+    #[default]
+    Wait,
+}
+
+#[derive(Debug, Validate, Clone, Serialize, Deserialize, JsonSchema)]
+#[validate(schema(function = "validate_schedule_trigger"))]
+pub struct KPAppScheduleItem {
+    pub name: String,
+    #[serde(default)]
+    #[validate(custom(function = "schedule_time"))]
+    pub start: Option<String>,
+    #[serde(default)]
+    #[validate(custom(function = "cron_expression"))]
+    pub cron: Option<String>,
+    #[serde(default)]
+    pub policy: KPAppSchedulePolicy,
+    #[serde(default)]
+    pub mode: KPAppResourceMode,
+    #[serde(deserialize_with = "resource_list")]
+    #[validate(custom(function = "validate_unique_names"))]
+    #[validate(nested)]
+    pub list: Vec<KPAppResourceItem>,
+}
+
+impl KPAppScheduleItem {
+    pub fn to_resource(&self) -> KPAppResource {
+        KPAppResource {
+            name: self.name.clone(),
+            mode: self.mode.clone(),
+            list: self.list.clone(),
+        }
+    }
+}
+
+#[derive(Debug, Validate, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct KPAppSchedule {
+    #[validate(custom(function = "validate_unique_schedule_names"))]
+    #[validate(nested)]
+    pub list: Vec<KPAppScheduleItem>,
+    #[serde(default)]
+    #[validate(nested)]
+    pub fallback: Option<KPAppResource>,
+}
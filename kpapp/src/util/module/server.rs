@@ -0,0 +1,62 @@
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+use serde::{Deserialize, Serialize};
+use schemars::JsonSchema;
+use validator::Validate;
+use crate::util::module::validator::server::validate_server_ports;
+
+#[derive(Debug, Validate, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct KPAppServerListener {
+    #[validate(range(min = 1, max = 65535))]
+    pub port: usize,
+}
+
+#[derive(Debug, Validate, Clone, Serialize, Deserialize, JsonSchema)]
+#[validate(schema(function = "validate_server_ports"))]
+pub struct KPAppServer {
+    #[serde(default = "default_server_address")]
+    pub address: IpAddr,
+    #[serde(default = "default_server_port")]
+    #[validate(range(min = 1, max = 65535))]
+    pub port: usize,
+    #[serde(default = "default_server_gop_number")]
+    #[validate(range(min = 1, max = 1000))]
+    pub gop_number: usize,
+    #[serde(default)]
+    #[validate(nested)]
+    pub httpflv: Option<KPAppServerListener>,
+    #[serde(default)]
+    #[validate(nested)]
+    pub hls: Option<KPAppServerListener>,
+}
+
+fn default_server_address() -> IpAddr { IpAddr::V4(Ipv4Addr::UNSPECIFIED) }
+fn default_server_port() -> usize { 1935 }
+fn default_server_gop_number() -> usize { 29 }
+
+impl Default for KPAppServer {
+    fn default() -> Self {
+        KPAppServer {
+            address: default_server_address(),
+            port: default_server_port(),
+            gop_number: default_server_gop_number(),
+            httpflv: None,
+            hls: None,
+        }
+    }
+}
+
+impl KPAppServer {
+    // the transcoder publishes through loopback when the server listens on every interface
+    pub fn get_local_url<T: ToString>(&self, app: T, stream: T) -> String {
+        let host = match self.address {
+            IpAddr::V4(address) if address.is_unspecified() => IpAddr::V4(Ipv4Addr::LOCALHOST),
+            IpAddr::V6(address) if address.is_unspecified() => IpAddr::V6(Ipv6Addr::LOCALHOST),
+            address => address,
+        };
+        let host = match host {
+            IpAddr::V4(address) => address.to_string(),
+            IpAddr::V6(address) => format!("[{}]", address),
+        };
+        format!("rtmp://{}:{}/{}/{}", host, self.port, app.to_string(), stream.to_string())
+    }
+}
@@ -0,0 +1,23 @@
+use std::time::Duration;
+use serde::{Deserialize, Serialize};
+use schemars::JsonSchema;
+use validator::Validate;
+use crate::util::module::deserialize::duration::{duration, serialize_duration, duration_schema};
+
+// the process exits by force once timeout passes after the first signal
+#[derive(Debug, Validate, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct KPAppShutdown {
+    #[serde(default = "default_shutdown_timeout", deserialize_with = "duration", serialize_with = "serialize_duration")]
+    #[schemars(schema_with = "duration_schema")]
+    pub timeout: Duration,
+}
+
+fn default_shutdown_timeout() -> Duration { Duration::from_secs(10) }
+
+impl Default for KPAppShutdown {
+    fn default() -> Self {
+        KPAppShutdown {
+            timeout: default_shutdown_timeout(),
+        }
+    }
+}
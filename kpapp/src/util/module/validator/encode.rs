@@ -0,0 +1,23 @@
+use anyhow::Result;
+use validator::ValidationError;
+use kpcodec::util::encode_parameter::KPEncodeParameter;
+use crate::util::module::encode::{KPAppAudioEncode, KPAppVideoEncode, ENCODE_OUTPUT_FORMAT};
+
+fn encode_parameter(parameter: Result<KPEncodeParameter>, codec: &str) -> Result<(), ValidationError> {
+    if let Err(err) = parameter.and_then(|parameter| parameter.validate(ENCODE_OUTPUT_FORMAT)) {
+        return Err(ValidationError {
+            code: "invalid_encode_parameter".into(),
+            message: Some(format!("Encode parameter is not supported: {}", err).into()),
+            params: [("codec".into(), codec.into())].iter().cloned().collect(),
+        });
+    }
+    Ok(())
+}
+
+pub fn validate_video_encode(video: &KPAppVideoEncode) -> Result<(), ValidationError> {
+    encode_parameter(video.to_encode_parameter(), &video.codec)
+}
+
+pub fn validate_audio_encode(audio: &KPAppAudioEncode) -> Result<(), ValidationError> {
+    encode_parameter(audio.to_encode_parameter(), &audio.codec)
+}
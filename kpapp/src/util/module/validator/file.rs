@@ -2,6 +2,9 @@ use crate::util::module::validator::*;
 
 
 const VIDEO_EXTENSIONS: &[&str] = &["mp4", "mkv", "flv"];
+const AUDIO_EXTENSIONS: &[&str] = &["mp3", "aac", "m4a", "wav", "flac", "ogg"];
+const PLAYLIST_EXTENSIONS: &[&str] = &["m3u", "m3u8", "pls"];
+pub(crate) const IMAGE_EXTENSIONS: &[&str] = &["png", "jpg", "jpeg", "bmp", "webp"];
 
 pub fn exist_file(file_path: &str) -> Result<(), ValidationError> {
     if !fs::metadata(file_path).is_ok() {
@@ -14,6 +17,30 @@ pub fn exist_file(file_path: &str) -> Result<(), ValidationError> {
     Ok(())
 }
 
+pub fn exist_directory(dir_path: &str) -> Result<(), ValidationError> {
+    if !fs::metadata(dir_path).map(|m| m.is_dir()).unwrap_or(false) {
+        return Err(ValidationError {
+            code: "directory_not_exist".into(),
+            message: Some(format!("Directory does not exist: {}", dir_path).into()),
+            params: [("dir_path".into(), dir_path.into())].iter().cloned().collect(),
+        });
+    }
+    Ok(())
+}
+
+pub fn glob_patterns(patterns: &Vec<String>) -> Result<(), ValidationError> {
+    for pattern in patterns {
+        if let Err(err) = glob::Pattern::new(pattern) {
+            return Err(ValidationError {
+                code: "invalid_glob_pattern".into(),
+                message: Some(format!("Invalid glob pattern: {}, error: {}", pattern, err).into()),
+                params: [("pattern".into(), pattern.as_str().into())].iter().cloned().collect(),
+            });
+        }
+    }
+    Ok(())
+}
+
 pub fn video_extension(file_path: &str) -> Result<(), ValidationError> {
     if let Some(extension) = std::path::Path::new(file_path).extension().and_then(|e| e.to_str()) {
         if !VIDEO_EXTENSIONS.contains(&extension) {
@@ -32,3 +59,46 @@ pub fn video_extension(file_path: &str) -> Result<(), ValidationError> {
     }
     Ok(())
 }
+
+pub fn audio_extension(file_path: &str) -> Result<(), ValidationError> {
+    if let Some(extension) = std::path::Path::new(file_path).extension().and_then(|e| e.to_str()) {
+        if !AUDIO_EXTENSIONS.contains(&extension) && !VIDEO_EXTENSIONS.contains(&extension) {
+            return Err(ValidationError {
+                code: "file_not_audio".into(),
+                message: Some(format!("File is not an audio: {}", file_path).into()),
+                params: [("file_path".into(), file_path.into())].iter().cloned().collect(),
+            });
+        }
+    } else {
+        return Err(ValidationError {
+            code: "file_no_extension".into(),
+            message: Some(format!("File has no extension: {}", file_path).into()),
+            params: [("file_path".into(), file_path.into())].iter().cloned().collect(),
+        });
+    }
+    Ok(())
+}
+
+pub fn playlist_extension(file_path: &str) -> Result<(), ValidationError> {
+    let extension = std::path::Path::new(file_path).extension().and_then(|e| e.to_str()).map(|e| e.to_lowercase());
+    if !extension.map_or(false, |extension| PLAYLIST_EXTENSIONS.contains(&extension.as_str())) {
+        return Err(ValidationError {
+            code: "file_not_playlist".into(),
+            message: Some(format!("File is not an m3u, m3u8 or pls playlist: {}", file_path).into()),
+            params: [("file_path".into(), file_path.into())].iter().cloned().collect(),
+        });
+    }
+    Ok(())
+}
+
+pub fn slate_extension(file_path: &str) -> Result<(), ValidationError> {
+    let extension = std::path::Path::new(file_path).extension().and_then(|e| e.to_str()).map(|e| e.to_lowercase());
+    if !extension.map_or(false, |extension| IMAGE_EXTENSIONS.contains(&extension.as_str()) || VIDEO_EXTENSIONS.contains(&extension.as_str())) {
+        return Err(ValidationError {
+            code: "file_not_slate".into(),
+            message: Some(format!("File is not an image or a video: {}", file_path).into()),
+            params: [("file_path".into(), file_path.into())].iter().cloned().collect(),
+        });
+    }
+    Ok(())
+}
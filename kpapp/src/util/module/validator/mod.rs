@@ -3,4 +3,8 @@ use std::fs;
 
 pub(crate) mod file;
 pub(crate) mod protocol;
-pub(crate) mod resource;
\ No newline at end of file
+pub(crate) mod resource;
+pub(crate) mod schedule;
+pub(crate) mod encode;
+pub(crate) mod output;
+pub(crate) mod server;
\ No newline at end of file
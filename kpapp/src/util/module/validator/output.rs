@@ -0,0 +1,13 @@
+use std::collections::HashSet;
+use validator::ValidationError;
+use crate::util::module::output::KPAppOutput;
+
+pub fn validate_unique_outputs(outputs: &Vec<KPAppOutput>) -> Result<(), ValidationError> {
+    let mut seen = HashSet::new();
+    for output in outputs {
+        if !seen.insert(&output.name) {
+            return Err(ValidationError::new("duplicate_name"));
+        }
+    }
+    Ok(())
+}
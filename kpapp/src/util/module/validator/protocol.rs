@@ -12,6 +12,20 @@ pub fn rtmp_url(url: &str) -> Result<(), ValidationError> {
     }
 }
 
+const NETWORK_SCHEMES: &[&str] = &["rtmp://", "rtmps://", "http://", "https://", "srt://"];
+
+pub fn network_url(url: &str) -> Result<(), ValidationError> {
+    if NETWORK_SCHEMES.iter().any(|scheme| url.starts_with(scheme)) && url.len() > url.find("://").unwrap() + 3 {
+        Ok(())
+    } else {
+        Err(ValidationError {
+            code: "url_not_network".into(),
+            message: Some(format!("URL is not a supported network URL (rtmp, http(s), srt, m3u8): {}", url).into()),
+            params: [("url".into(), url.into())].iter().cloned().collect(),
+        })
+    }
+}
+
 pub fn file_url(url: &str) -> Result<(), ValidationError> {
     if url.starts_with("file://") {
         Ok(())
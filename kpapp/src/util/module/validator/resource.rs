@@ -1,6 +1,6 @@
 use std::collections::HashSet;
 use validator::ValidationError;
-use crate::util::module::resource::KPAppResourceItem;
+use crate::util::module::resource::{KPAppResourceItem, SingleDetail};
 
 // Custom validation function for unique names in KPAppResourceItem
 pub fn validate_unique_names(items: &Vec<KPAppResourceItem>) -> Result<(), ValidationError> {
@@ -11,4 +11,19 @@ pub fn validate_unique_names(items: &Vec<KPAppResourceItem>) -> Result<(), Valid
         }
     }
     Ok(())
-}
\ No newline at end of file
+}
+
+pub fn validate_trim(single: &SingleDetail) -> Result<(), ValidationError> {
+    if single.end.is_some() && single.duration.is_some() {
+        return Err(ValidationError::new("trim_end_and_duration"));
+    }
+    if single.duration.is_some() && single.get_end_point().is_none() {
+        return Err(ValidationError::new("trim_overflow"));
+    }
+    if let (Some(start), Some(end)) = (single.start, single.get_end_point()) {
+        if end <= start {
+            return Err(ValidationError::new("trim_end_before_start"));
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,48 @@
+use std::collections::HashSet;
+use std::str::FromStr;
+use chrono::NaiveTime;
+use validator::ValidationError;
+use crate::util::module::schedule::KPAppScheduleItem;
+
+pub fn parse_schedule_time(time: &str) -> Option<NaiveTime> {
+    NaiveTime::parse_from_str(time, "%H:%M:%S").or_else(|_| NaiveTime::parse_from_str(time, "%H:%M")).ok()
+}
+
+pub fn schedule_time(time: &str) -> Result<(), ValidationError> {
+    if parse_schedule_time(time).is_none() {
+        return Err(ValidationError {
+            code: "invalid_schedule_time".into(),
+            message: Some(format!("Schedule time is not HH:MM or HH:MM:SS: {}", time).into()),
+            params: [("time".into(), time.into())].iter().cloned().collect(),
+        });
+    }
+    Ok(())
+}
+
+pub fn cron_expression(expression: &str) -> Result<(), ValidationError> {
+    if let Err(err) = cron::Schedule::from_str(expression) {
+        return Err(ValidationError {
+            code: "invalid_cron_expression".into(),
+            message: Some(format!("Invalid cron expression: {}, error: {}", expression, err).into()),
+            params: [("cron".into(), expression.into())].iter().cloned().collect(),
+        });
+    }
+    Ok(())
+}
+
+pub fn validate_schedule_trigger(item: &KPAppScheduleItem) -> Result<(), ValidationError> {
+    if item.start.is_some() == item.cron.is_some() {
+        return Err(ValidationError::new("schedule_start_or_cron"));
+    }
+    Ok(())
+}
+
+pub fn validate_unique_schedule_names(items: &Vec<KPAppScheduleItem>) -> Result<(), ValidationError> {
+    let mut seen = HashSet::new();
+    for item in items {
+        if !seen.insert(&item.name) {
+            return Err(ValidationError::new("duplicate_name"));
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,18 @@
+use std::collections::HashSet;
+use validator::ValidationError;
+use crate::util::module::server::KPAppServer;
+
+pub fn validate_server_ports(server: &KPAppServer) -> Result<(), ValidationError> {
+    let mut seen = HashSet::new();
+    let ports = [Some(server.port), server.httpflv.as_ref().map(|listener| listener.port), server.hls.as_ref().map(|listener| listener.port)];
+    for port in ports.into_iter().flatten() {
+        if !seen.insert(port) {
+            return Err(ValidationError {
+                code: "duplicate_port".into(),
+                message: Some(format!("Server port is used by more than one listener: {}", port).into()),
+                params: [("port".into(), port.into())].iter().cloned().collect(),
+            });
+        }
+    }
+    Ok(())
+}
@@ -1,5 +1,6 @@
-use std::ffi::c_char;
+use std::ffi::{c_char, c_void};
 use std::slice::Iter;
+use std::sync::Once;
 use crate::decode::*;
 use crate::filter::graph_source::{KPGraphSourceAttribute, KPGraphSourceRely};
 use crate::util::encode_parameter::{KPEncodeParameter, KPEncodeParameterPreset, KPEncodeParameterProfile};
@@ -11,6 +12,48 @@ pub struct KPDecodeStreamContext {
     codec_context_ptr: KPAVCodecContext,
     end_of_file: bool,
     metadata: BTreeMap<String, String>,
+
+    // codec parameters
+    codec_id: KPAVCodecId,
+    bit_rate: u64,
+    width: usize,
+    height: usize,
+    sample_rate: usize,
+    channels: usize,
+}
+
+impl KPDecodeStreamContext {
+    pub fn get_media_type(&self) -> &KPAVMediaType {
+        &self.media_type
+    }
+
+    pub fn get_time_base(&self) -> &KPAVRational {
+        &self.time_base
+    }
+
+    pub fn get_metadata(&self) -> &BTreeMap<String, String> {
+        &self.metadata
+    }
+
+    pub fn get_codec_id(&self) -> &KPAVCodecId {
+        &self.codec_id
+    }
+
+    pub fn get_bit_rate(&self) -> u64 {
+        self.bit_rate
+    }
+
+    pub fn get_resolution(&self) -> (usize, usize) {
+        (self.width, self.height)
+    }
+
+    pub fn get_sample_rate(&self) -> usize {
+        self.sample_rate
+    }
+
+    pub fn get_channels(&self) -> usize {
+        self.channels
+    }
 }
 
 #[derive(Default, Debug)]
@@ -22,8 +65,12 @@ pub struct KPDecode {
     format_context_ptr: KPAVFormatContext,
 
     // open options
-    open_timeout: usize,
+    input_format: Option<String>,
+    open_timeout: Duration,
+    read_timeout: Duration,
+    interrupt_deadline: Box<i64>,
     start_point: Option<Duration>,
+    resume_point: Option<Duration>,
     end_point: Option<Duration>,
     expect_stream_index: HashMap<KPAVMediaType, Option<usize>>,
     encode_hardware: bool,
@@ -33,24 +80,33 @@ pub struct KPDecode {
 
     // media information
     format_name: String,
-    metadata: HashMap<String, String>,
+    metadata: BTreeMap<String, String>,
     streams: BTreeMap<usize, KPDecodeStreamContext>,
     start_time: Duration,
-    duration: Duration,
+    pub(super) duration: Duration,
     bit_rate: u64,
 
     // state
     pub(super) status: KPCodecStatus,
     pub(super) position: Duration,
+    media_position: Duration,
     lead_stream_index: Option<usize>,
     enable_loop_count: usize,
-    loop_latest_pts: i64,
-    loop_latest_dts: i64,
-    loop_gradient_pts: i64,
-    loop_gradient_dts: i64,
+    loop_latest_timestamp: i64,
+    loop_gradient_timestamp: i64,
 
     // cache
     packet: KPAVPacket,
+    drained_frames: LinkedList<(KPAVMediaType, KPAVFrame)>,
+}
+
+static DEVICE_INIT: Once = Once::new();
+
+// abort blocking io once the deadline of the current operation has passed
+unsafe extern "C" fn decode_interrupt_callback(opaque: *mut c_void) -> c_int {
+    if opaque.is_null() { return 0; }
+    let deadline = *(opaque as *const i64);
+    (deadline > 0 && av_gettime_relative() > deadline) as c_int
 }
 
 pub struct KPDecodeIterator<'a> {
@@ -120,15 +176,17 @@ impl KPGraphSourceRely for KPDecode {
 
 impl KPDecode {
     pub fn new<T: ToString>(input_path: T) -> Self {
-        let open_timeout = 10;
+        let open_timeout = Duration::from_secs(10);
+        let read_timeout = Duration::from_secs(10);
         let mut format_context_options = HashMap::new();
         format_context_options.insert(String::from("scan_all_pmts"), String::from("1"));
-        format_context_options.insert(String::from("rw_timeout"), String::from(open_timeout.to_string()));
+        format_context_options.insert(String::from("rw_timeout"), read_timeout.as_micros().to_string());
 
         KPDecode {
             input_path: input_path.to_string(),
             format_context_options,
             open_timeout,
+            read_timeout,
             packet: KPAVPacket::new(),
             enable_loop: false,
             ..Default::default()
@@ -150,10 +208,32 @@ impl KPDecode {
             let mut open_options = KPAVDictionary::new(&self.format_context_options);
             let mut open_options_ptr = open_options.get();
 
-            let mut format_context_ptr: *mut AVFormatContext = ptr::null_mut();
+            // virtual inputs such as lavfi are registered by libavdevice, looked up before the context is allocated so an error leaks nothing
+            let input_format_ptr = match &self.input_format {
+                Some(input_format) => {
+                    DEVICE_INIT.call_once(|| unsafe { avdevice_register_all() });
+                    let format_name: CString = cstring!(input_format.clone());
+                    let input_format_ptr = unsafe { av_find_input_format(format_name.as_ptr()) };
+                    if input_format_ptr.is_null() { return Err(anyhow!("find input format failed. format: {}", input_format)); }
+                    input_format_ptr
+                }
+                None => ptr::null(),
+            };
             let filepath: CString = cstring!(self.input_path.clone());
+
+            let mut format_context_ptr: *mut AVFormatContext = unsafe { avformat_alloc_context() };
+            if format_context_ptr.is_null() { return Err(anyhow!("alloc format context failed")); }
+            unsafe {
+                (*format_context_ptr).interrupt_callback = AVIOInterruptCB {
+                    callback: Some(decode_interrupt_callback),
+                    opaque: self.interrupt_deadline.as_mut() as *mut i64 as *mut c_void,
+                };
+            }
+            self.set_deadline(self.open_timeout);
+
+            // the context is freed by avformat_open_input on failure
             let ret = unsafe {
-                avformat_open_input(&mut format_context_ptr, filepath.as_ptr(), ptr::null_mut(), &mut open_options_ptr)
+                avformat_open_input(&mut format_context_ptr, filepath.as_ptr(), input_format_ptr, &mut open_options_ptr)
             };
             if ret < 0 { return Err(anyhow!("open input failed. error: {:?}",  averror!(ret))); }
             open_options.set(open_options_ptr);
@@ -173,6 +253,7 @@ impl KPDecode {
         });
         self.duration = Duration::from_micros(format_context.duration as u64);
         self.bit_rate = format_context.bit_rate as u64;
+        self.metadata = KPAVDictionary::from(format_context.metadata);
 
         self.status = KPCodecStatus::Opened;
         info!("open file success. path:{}, format_name:{}, start_time:{:?}, duration:{:?}, bit_rate:{}",
@@ -182,6 +263,7 @@ impl KPDecode {
 
     pub fn find_streams(&mut self) -> Result<()> {
         assert_eq!(self.status, KPCodecStatus::Opened);
+        self.set_deadline(self.open_timeout);
         let ret = unsafe { avformat_find_stream_info(self.format_context_ptr.as_ptr(), ptr::null_mut()) };
         if ret < 0 { return Err(anyhow!("find streams failed. error: {:?}", averror!(ret))); }
 
@@ -197,12 +279,19 @@ impl KPDecode {
 
                 let stream = *stream_ptr;
                 let metadata = KPAVDictionary::from(stream.metadata);
+                let codec_parameters = *stream.codecpar;
                 let codec_context = KPDecodeStreamContext {
-                    media_type: KPAVMediaType::from((*stream.codecpar).codec_type),
+                    media_type: KPAVMediaType::from(codec_parameters.codec_type),
                     time_base: KPAVRational::from(stream.time_base),
                     codec_context_ptr: Default::default(),
                     end_of_file: false,
                     metadata,
+                    codec_id: KPAVCodecId::from(codec_parameters.codec_id),
+                    bit_rate: codec_parameters.bit_rate as u64,
+                    width: codec_parameters.width as usize,
+                    height: codec_parameters.height as usize,
+                    sample_rate: codec_parameters.sample_rate as usize,
+                    channels: codec_parameters.channels as usize,
                 };
 
                 self.streams.insert(i, codec_context);
@@ -306,9 +395,11 @@ impl KPDecode {
         self.lead_stream_index = Some(lead_stream_index);
 
         // set start point
-        if let Some(start_point) = self.start_point {
+        if self.get_seek_point().is_some() {
+            let start_timestamp = self.get_start_timestamp()?;
             let stream = self.streams.get(&lead_stream_index).unwrap();
-            let seek_timestamp = unsafe { av_rescale_q(start_point.as_micros() as i64, AV_TIME_BASE_Q, stream.time_base.get()) };
+            let seek_timestamp = unsafe { av_rescale_q(start_timestamp.as_micros() as i64, AV_TIME_BASE_Q, stream.time_base.get()) };
+            self.set_deadline(self.open_timeout);
             let ret = unsafe { av_seek_frame(self.format_context_ptr.get(), lead_stream_index as c_int, seek_timestamp, AVSEEK_FLAG_BACKWARD as c_int) };
             if ret < 0 {
                 return Err(anyhow!("seek start point failed. error:{:?}", averror!(ret)));
@@ -324,28 +415,81 @@ impl KPDecode {
         Ok(())
     }
 
+    // the resume point applies to the first pass only, loops go back to the start point
+    fn get_seek_point(&self) -> Option<Duration> {
+        self.resume_point.or(self.start_point)
+    }
+
+    // media start time plus the seek point, none of them may overflow
+    fn get_start_timestamp(&self) -> Result<Duration> {
+        let start_point = self.get_seek_point().unwrap_or_default();
+        self.start_time.checked_add(start_point).ok_or_else(|| anyhow!("start point overflow. start_time: {:?}, start_point: {:?}", self.start_time, start_point))
+    }
+
+    fn set_deadline(&mut self, timeout: Duration) {
+        *self.interrupt_deadline = unsafe { av_gettime_relative() } + timeout.as_micros() as i64;
+    }
+
+    // seek back to the start point and continue the timeline from the latest packet
+    fn loop_point(&mut self) -> Result<()> {
+        if self.start_point.is_none() {
+            self.start_point = Some(Duration::from_secs(0));
+        }
+        self.resume_point = None;
+        self.drain_codec()?;
+        self.set_point()?;
+
+        self.enable_loop_count += 1;
+        let start_timestamp = self.get_start_timestamp()?.as_micros() as i64;
+        self.loop_gradient_timestamp = self.loop_latest_timestamp - start_timestamp;
+        debug!("decode loop. count: {}, gradient: {}", self.enable_loop_count, self.loop_gradient_timestamp);
+        Ok(())
+    }
+
+    // keep the frames of the finished pass before the codec is reset, they are returned ahead of the next pass
+    fn drain_codec(&mut self) -> Result<()> {
+        for (media_type, expect_stream_index) in self.expect_stream_index.iter() {
+            let stream_context = self.streams.get(&expect_stream_index.unwrap()).unwrap();
+            if stream_context.codec_context_ptr.is_null() || stream_context.end_of_file {
+                continue;
+            }
+            let ret = unsafe { avcodec_send_packet(stream_context.codec_context_ptr.get(), ptr::null_mut()) };
+            if ret < 0 {
+                return Err(anyhow!("drain codec failed. error:{:?}", averror!(ret)));
+            }
+            loop {
+                let frame = KPAVFrame::new();
+                let ret = unsafe { avcodec_receive_frame(stream_context.codec_context_ptr.get(), frame.get()) };
+                match ret {
+                    _ if ret >= 0 => self.drained_frames.push_back((media_type.clone(), frame)),
+                    _ if ret == AVERROR_EOF || ret == AVERROR(EAGAIN) => break,
+                    r => return Err(anyhow!("receipt from codec failed. index:{}, error:{:?}", expect_stream_index.unwrap(), averror!(r))),
+                }
+            }
+        }
+        Ok(())
+    }
+
     pub fn stream_to_codec(&mut self) -> Result<()> {
         assert_eq!(self.status, KPCodecStatus::Started);
         assert!(self.lead_stream_index.is_some());
         assert!((*self.packet.get()).buf.is_null());
         let lead_stream_index = self.lead_stream_index.unwrap();
 
+        // hand out the frames kept from the previous pass before reading on
+        if !self.drained_frames.is_empty() {
+            return Ok(());
+        }
+
         // read a packet
+        self.set_deadline(self.read_timeout);
         let ret = unsafe { av_read_frame(self.format_context_ptr.get(), self.packet.get()) };
         if ret < 0 {
             return match ret {
                 AVERROR_EOF => {
                     // enable loop
                     if self.enable_loop {
-                        if self.start_point.is_none() {
-                            self.start_point = Some(Duration::from_secs(0));
-                        }
-                        self.set_point()?;
-
-                        self.enable_loop_count += 1;
-                        self.loop_gradient_pts = std::cmp::max(self.loop_latest_pts, self.loop_latest_dts);
-                        self.loop_gradient_dts = std::cmp::max(self.loop_latest_pts, self.loop_latest_dts);
-                        return Ok(());
+                        return self.loop_point();
                     }
 
                     // set eof
@@ -356,6 +500,7 @@ impl KPDecode {
                     }
                     Ok(())
                 }
+                AVERROR_EXIT => { Err(anyhow!("stream packet timeout. timeout: {:?}", self.read_timeout)) }
                 _ => { Err(anyhow!("stream packet failed. error: {:?}", averror!(ret))) }
             };
         }
@@ -373,25 +518,40 @@ impl KPDecode {
             return Ok(());
         }
 
-        packet.pts += self.loop_gradient_pts;
-        packet.dts += self.loop_gradient_dts;
-        self.loop_latest_pts = packet.pts;
-        self.loop_latest_dts = packet.dts;
-
-        // set state
-        if packet.stream_index as usize == lead_stream_index {
-            let stream_context = self.streams.get(&self.lead_stream_index.unwrap()).unwrap();
-            self.position = Duration::from_secs_f64(av_q2d(stream_context.time_base.get()) as f64 * packet.pts as f64 + self.start_time.as_micros() as f64);
-        }
+        let stream_time_base = self.streams.get(&(packet.stream_index as usize)).unwrap().time_base.get();
+        let start_timestamp = self.start_time.as_micros() as i64;
+        let media_timestamp = unsafe { av_rescale_q(packet.pts, stream_time_base, AV_TIME_BASE_Q) } - start_timestamp;
 
         // compare end_point
         if let Some(end_point) = self.end_point {
-            if self.position > end_point {
+            if packet.stream_index as usize == lead_stream_index && media_timestamp > end_point.as_micros() as i64 {
+                self.packet.clean();
+                if self.enable_loop {
+                    return self.loop_point();
+                }
                 self.status = KPCodecStatus::Ended;
+                for (_, expect_stream_index) in self.expect_stream_index.iter() {
+                    let stream_context = self.streams.get_mut(&expect_stream_index.unwrap()).unwrap();
+                    stream_context.codec_context_ptr.flush()?;
+                }
                 return Ok(());
             }
         }
 
+        // loop gradient
+        let loop_gradient = unsafe { av_rescale_q(self.loop_gradient_timestamp, AV_TIME_BASE_Q, stream_time_base) };
+        packet.pts += loop_gradient;
+        packet.dts += loop_gradient;
+        let packet_end_timestamp = unsafe { av_rescale_q(packet.pts + std::cmp::max(packet.duration, 0), stream_time_base, AV_TIME_BASE_Q) };
+        self.loop_latest_timestamp = std::cmp::max(self.loop_latest_timestamp, packet_end_timestamp);
+
+        // set state
+        if packet.stream_index as usize == lead_stream_index {
+            let position = unsafe { av_rescale_q(packet.pts, stream_time_base, AV_TIME_BASE_Q) } - start_timestamp;
+            self.position = Duration::from_micros(std::cmp::max(position, 0) as u64);
+            self.media_position = Duration::from_micros(std::cmp::max(media_timestamp, 0) as u64);
+        }
+
         // send to codec
         let stream_context = self.streams.get(&(packet.stream_index as usize)).unwrap();
         trace!("send packet to codec. position: {:?}, index:{}, media_type:{}, pts:{}, dts:{}, size:{}",self.position,self.packet.get().stream_index,stream_context.media_type,self.packet.get().pts,self.packet.get().dts, self.packet.get().size);
@@ -408,6 +568,12 @@ impl KPDecode {
 
     pub fn stream_from_codec(&mut self) -> Result<Option<(KPAVMediaType, KPAVFrame)>> {
         assert!(matches!(self.status, KPCodecStatus::Started|KPCodecStatus::Ended));
+
+        // frames of the previous pass skip the start point check, it belongs to the new pass
+        if let Some((media_type, frame)) = self.drained_frames.pop_front() {
+            METRICS.decode_frames.with_label_values(&[&media_type.to_string()]).inc();
+            return Ok(Some((media_type, frame)));
+        }
         if !self.expect_stream_index.iter().any(|(_, v)| {
             let stream_context = self.streams.get(&v.unwrap()).unwrap();
             !stream_context.end_of_file
@@ -416,6 +582,11 @@ impl KPDecode {
             return Ok(None);
         }
 
+        // drop frames before the exact start point after keyframe seek
+        let start_timestamp = self.start_time.as_micros() as i64;
+        let start_point_timestamp = self.get_seek_point().map(|start_point| start_point.as_micros() as i64);
+        let loop_gradient_timestamp = self.loop_gradient_timestamp;
+
         // receive expect stream context
         for (media_type, expect_stream_index) in self.expect_stream_index.iter() {
             let stream_index = expect_stream_index.unwrap();
@@ -430,6 +601,17 @@ impl KPDecode {
                 match ret {
                     _ if ret >= 0 => {
                         trace!("receipt frame. index:{}, media_type:{}, pts:{}", stream_index, media_type, frame.get().pts);
+                        if let Some(start_point_timestamp) = start_point_timestamp {
+                            let frame_pts = frame.get().pts;
+                            if frame_pts != AV_NOPTS_VALUE {
+                                let frame_timestamp = unsafe { av_rescale_q(frame_pts, stream_context.time_base.get(), AV_TIME_BASE_Q) } - loop_gradient_timestamp - start_timestamp;
+                                if frame_timestamp < start_point_timestamp {
+                                    trace!("drop frame before start point. index:{}, pts:{}", stream_index, frame_pts);
+                                    continue;
+                                }
+                            }
+                        }
+                        METRICS.decode_frames.with_label_values(&[&media_type.to_string()]).inc();
                         return Ok(Some((media_type.clone(), frame)));
                     }
                     _ if ret == AVERROR(EAGAIN) => {
@@ -456,9 +638,71 @@ impl KPDecode {
         &self.expect_stream_index
     }
 
+    pub fn get_format_name(&self) -> &String {
+        &self.format_name
+    }
+
+    pub fn get_start_time(&self) -> Duration {
+        self.start_time
+    }
+
+    // none when the input does not report a duration, e.g. a live stream
+    pub fn get_duration(&self) -> Option<Duration> {
+        if self.duration.as_micros() == AV_NOPTS_VALUE as u64 as u128 { return None; }
+        Some(self.duration)
+    }
+
+    pub fn get_bit_rate(&self) -> u64 {
+        self.bit_rate
+    }
+
+    pub fn get_metadata(&self) -> &BTreeMap<String, String> {
+        &self.metadata
+    }
+
+    pub fn get_streams(&self) -> &BTreeMap<usize, KPDecodeStreamContext> {
+        &self.streams
+    }
+
     pub fn set_enable_loop(&mut self, enable: bool) {
         self.enable_loop = enable;
     }
+
+    pub fn set_input_format<T: ToString>(&mut self, input_format: T) -> &mut Self {
+        self.input_format = Some(input_format.to_string());
+        self
+    }
+
+    pub fn set_timeout(&mut self, open_timeout: Duration, read_timeout: Duration) -> &mut Self {
+        self.open_timeout = open_timeout;
+        self.read_timeout = read_timeout;
+        self.format_context_options.insert(String::from("rw_timeout"), read_timeout.as_micros().to_string());
+        self
+    }
+
+    pub fn set_start_point(&mut self, start_point: Option<Duration>) -> &mut Self {
+        self.start_point = start_point;
+        self
+    }
+
+    pub fn set_resume_point(&mut self, resume_point: Option<Duration>) -> &mut Self {
+        self.resume_point = resume_point;
+        self
+    }
+
+    pub fn set_end_point(&mut self, end_point: Option<Duration>) -> &mut Self {
+        self.end_point = end_point;
+        self
+    }
+
+    // position in the media file without the loop gradient
+    pub fn get_media_position(&self) -> Duration {
+        self.media_position
+    }
+
+    pub fn get_loop_count(&self) -> usize {
+        self.enable_loop_count
+    }
 }
 
 impl KPDecode {
@@ -496,6 +740,35 @@ fn open_invalid_file() {
     assert!(decode.open().is_err());
 }
 
+#[test]
+fn open_network() {
+    initialize();
+    let mut decode = KPDecode::new(env::var("INPUT_NETWORK_URL").unwrap());
+    decode.set_timeout(Duration::from_secs(5), Duration::from_secs(5));
+    decode.open().unwrap();
+
+    // set expect stream
+    let mut expect_streams = HashMap::new();
+    expect_streams.insert(KPAVMediaType::from(AVMEDIA_TYPE_VIDEO), None);
+    expect_streams.insert(KPAVMediaType::from(AVMEDIA_TYPE_AUDIO), None);
+    decode.set_expect_stream(expect_streams);
+    decode.find_streams().unwrap();
+    decode.open_codec().unwrap();
+
+    for get_frame in decode.iter().take(100) {
+        let (media_type, frame) = get_frame.unwrap();
+        info!("get frame. {:?}, meida_type: {}", frame, media_type);
+    }
+}
+
+#[test]
+fn open_network_timeout() {
+    initialize();
+    let mut decode = KPDecode::new("rtmp://127.0.0.1:1/live/none");
+    decode.set_timeout(Duration::from_secs(1), Duration::from_secs(1));
+    assert!(decode.open().is_err());
+}
+
 #[test]
 fn decode_loop() {
     initialize();
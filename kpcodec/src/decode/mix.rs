@@ -11,6 +11,7 @@ pub struct KPMixCodec {
     // options
     expect_stream_index: HashMap<KPAVMediaType, Option<usize>>,
     encode_hardware: bool,
+    enable_loop: bool,
 
     // state
     status: KPCodecStatus,
@@ -26,9 +27,14 @@ impl<'a> Iterator for KPMixCodecIterator<'a> {
     fn next(&mut self) -> Option<Self::Item> {
         let mix = &mut self.mix;
 
+        let maintainer_source = mix.maintainer_source.unwrap();
         loop {
-            for (_, decode) in mix.source.iter() {
+            // the item ends with the maintainer, other sources are looped
+            for (media_type, decode) in mix.source.iter() {
                 if decode.status == KPCodecStatus::Ended {
+                    if media_type.ne(&maintainer_source) {
+                        warn!("mix source ended before maintainer. media_type: {}", media_type);
+                    }
                     mix.status = KPCodecStatus::Ended;
                     return None;
                 }
@@ -83,6 +89,7 @@ impl KPMixCodec {
             maintainer_source,
             expect_stream_index: HashMap::new(),
             encode_hardware: false,
+            enable_loop: false,
             status: Default::default(),
         }
     }
@@ -93,25 +100,33 @@ impl KPMixCodec {
         self
     }
 
+    pub fn set_enable_loop(&mut self, enable: bool) {
+        self.enable_loop = enable;
+    }
+
     pub fn open(&mut self) -> Result<()> {
         for (media_type, decode) in self.source.iter_mut() {
             // open file
             decode.open()?;
 
             // set expect stream
-            if let Some(expect_stream) = self.expect_stream_index.get(media_type) {
-                let mut expect_streams = HashMap::new();
-                expect_streams.insert(media_type.clone(), expect_stream.clone());
-                decode.set_expect_stream(expect_streams);
-            }
+            let mut expect_streams = HashMap::new();
+            expect_streams.insert(media_type.clone(), self.expect_stream_index.get(media_type).cloned().flatten());
+            decode.set_expect_stream(expect_streams);
+        }
 
-            // set maintainer loop
-            if let Some(maintainer_media_type) = self.maintainer_source {
-                if maintainer_media_type.ne(media_type) {
-                    decode.set_enable_loop(true);
-                }
-            }
+        // the longer source is the maintainer by default, a source without a known duration never wins over one with it
+        if self.maintainer_source.is_none() {
+            let (media_type, _) = self.source.iter().max_by_key(|(_, decode)| decode.get_duration()).unwrap();
+            self.maintainer_source = Some(media_type.clone());
+        }
+
+        // set maintainer loop
+        let maintainer_source = self.maintainer_source.unwrap();
+        for (media_type, decode) in self.source.iter_mut() {
+            decode.set_enable_loop(self.enable_loop || maintainer_source.ne(media_type));
         }
+        debug!("mix source maintainer. media_type: {}", maintainer_source);
 
         self.status = KPCodecStatus::Opened;
         Ok(())
@@ -136,6 +151,19 @@ impl KPMixCodec {
     pub fn get_status(&self) -> &KPCodecStatus {
         &self.status
     }
+
+    pub fn get_expect_streams(&self) -> HashMap<KPAVMediaType, Option<usize>> {
+        self.source.iter().map(|(media_type, decode)| {
+            (media_type.clone(), decode.get_expect_streams().get(media_type).cloned().flatten())
+        }).collect()
+    }
+
+    pub fn get_loop_count(&self) -> usize {
+        match self.maintainer_source {
+            None => 0,
+            Some(maintainer_source) => self.source.get(&maintainer_source).unwrap().get_loop_count(),
+        }
+    }
 }
 
 #[test]
@@ -15,6 +15,7 @@ use std::collections::{BTreeMap, LinkedList};
 use std::ffi::c_int;
 use log::{debug, warn};
 use log::trace;
+use crate::util::metrics::METRICS;
 
 pub mod decode;
 pub mod mix;
\ No newline at end of file
@@ -29,6 +29,9 @@ impl<'a> Iterator for KPEncodeIterator<'a> {
 
         // stream to queue
         encode.stream_from_encode().unwrap();
+        for (_, stream_context) in encode.streams.iter() {
+            METRICS.encode_packet_queue.with_label_values(&[&stream_context.media_type.to_string()]).set(stream_context.packets.len() as i64);
+        }
 
         let lead_stream_index = encode.lead_stream_index;
         if let Some((_, lead_dts)) = encode.maintainer {
@@ -321,6 +324,7 @@ impl KPEncode {
 
         let ret = unsafe { avcodec_send_frame(stream_context.codec_context_ptr.get(), frame.get()) };
         if ret < 0 { return Err(anyhow!("stream to encode failed. error: {:?}", averror!(ret))); }
+        METRICS.encode_frames.with_label_values(&[&media_type.to_string()]).inc();
         Ok(())
     }
 
@@ -368,6 +372,7 @@ impl KPEncode {
                             if packet.is_valid() && packet.get().pts >= 0 {
                                 let interval_time = unsafe { av_gettime() } - sync_timestamp;
                                 let current_packet_timestamp = unsafe { av_rescale_q(packet.get().pts, stream_time_base.get(), AV_TIME_BASE_Q) };
+                                METRICS.pacing_lag.set(std::cmp::max(interval_time - current_packet_timestamp, 0) as f64 / AV_TIME_BASE as f64);
                                 if current_packet_timestamp > interval_time {
                                     let sleep_sec = current_packet_timestamp - interval_time;
                                     trace!("usleep sec for sync timestamp. sec: {}", sleep_sec);
@@ -1,5 +1,8 @@
 use crate::encode::encode::KPEncode;
 use crate::encode::*;
+use std::time::Instant;
+
+const BITRATE_WINDOW: Duration = Duration::from_secs(1);
 
 #[derive(Default)]
 pub struct KPLinker {
@@ -11,21 +14,16 @@ pub struct KPLinker {
     latest_packet_dts: i64,
     gradient_packet_dts: i64,
     is_gradient_ascent: bool,
+
+    // bitrate window
+    window_start: Option<Instant>,
+    window_bytes: u64,
 }
 
 impl Drop for KPLinker {
     fn drop(&mut self) {
-        if self.encode.status != KPCodecStatus::None {
-            self.encode.flush().unwrap();
-
-            // set flush end flags
-            {
-                self.encode.status = KPCodecStatus::Stopped;
-                for (_, stream_context) in self.encode.streams.iter_mut() {
-                    stream_context.end_of_file = true;
-                }
-            }
-            self.encode.write_trailer().unwrap();
+        if let Err(err) = self.close() {
+            error!("close linker failed. error: {}", err);
         }
     }
 }
@@ -55,28 +53,66 @@ impl KPLinker {
                 return Ok(());
             }
 
+            // rebase the first key packet of the next item onto the end of the previous one
+            let first_packet_ts = std::cmp::max(std::cmp::min(packet.get().pts, packet.get().dts), 0);
+            self.gradient_packet_pts = self.latest_packet_pts - first_packet_ts;
+            self.gradient_packet_dts = self.latest_packet_dts - first_packet_ts;
             self.is_gradient_ascent = false;
         }
 
         // set latest ascent state
         packet.get().pts = packet.get().pts + self.gradient_packet_pts;
         packet.get().dts = packet.get().dts + self.gradient_packet_dts;
-        self.latest_packet_pts = std::cmp::max(self.latest_packet_pts, packet.get().pts);
-        self.latest_packet_dts = std::cmp::max(self.latest_packet_dts, packet.get().dts);
+        let packet_duration = std::cmp::max(packet.get().duration, 0);
+        self.latest_packet_pts = std::cmp::max(self.latest_packet_pts, packet.get().pts + packet_duration);
+        self.latest_packet_dts = std::cmp::max(self.latest_packet_dts, packet.get().dts + packet_duration);
 
         // write packet
+        let packet_size = std::cmp::max(packet.get().size, 0) as u64;
         self.encode.write(&packet)?;
+        self.update_bitrate(packet_size);
 
         Ok(())
     }
 
+    fn update_bitrate(&mut self, packet_size: u64) {
+        METRICS.output_bytes.inc_by(packet_size);
+        self.window_bytes += packet_size;
+
+        let window_start = *self.window_start.get_or_insert_with(Instant::now);
+        let elapsed = window_start.elapsed();
+        if elapsed >= BITRATE_WINDOW {
+            METRICS.output_bitrate.set(self.window_bytes as f64 * 8.0 / elapsed.as_secs_f64());
+            self.window_start = Some(Instant::now());
+            self.window_bytes = 0;
+        }
+    }
+
     pub fn gradient_ascent(&mut self) {
+        // offset is resolved on the first key packet of the next item
         let max = std::cmp::max(self.latest_packet_pts, self.latest_packet_dts);
-        self.gradient_packet_pts = max + 1000;
-        self.gradient_packet_dts = max + 1000;
+        self.latest_packet_pts = max;
+        self.latest_packet_dts = max;
         self.is_gradient_ascent = true;
     }
 
+    // flush and write the trailer once, later calls are no-ops
+    pub fn close(&mut self) -> Result<()> {
+        if matches!(self.encode.status, KPCodecStatus::None | KPCodecStatus::Ended) {
+            return Ok(());
+        }
+        self.encode.flush()?;
+
+        // set flush end flags
+        {
+            self.encode.status = KPCodecStatus::Stopped;
+            for (_, stream_context) in self.encode.streams.iter_mut() {
+                stream_context.end_of_file = true;
+            }
+        }
+        self.encode.write_trailer()
+    }
+
     pub fn get_output_path(&self) -> String {
         self.encode.output_path.clone()
     }
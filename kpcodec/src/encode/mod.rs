@@ -13,7 +13,7 @@ use std::env;
 use log::info;
 use std::collections::{BTreeMap, LinkedList};
 use std::ffi::c_int;
-use log::{debug, warn};
+use log::{debug, error, warn};
 use std::collections::VecDeque;
 use log::trace;
 use std::ffi::c_char;
@@ -24,6 +24,7 @@ use std::collections::btree_map::Iter;
 use crate::filter::graph::KPGraph;
 use crate::filter::graph::KPGraphStatus;
 use crate::util::encode_parameter::KPEncodeParameter;
+use crate::util::metrics::METRICS;
 
 pub mod encode;
 pub mod linker;
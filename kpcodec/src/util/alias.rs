@@ -528,6 +528,14 @@ impl KPAVPixelFormat {
         KPAVPixelFormat(pix_fmt)
     }
 
+    pub fn from_name(name: &str) -> Result<Self> {
+        let pix_fmt = unsafe { av_get_pix_fmt(cstring!(name).as_ptr()) };
+        if pix_fmt == AV_PIX_FMT_NONE {
+            return Err(anyhow!("unknown pixel format. name: {}", name));
+        }
+        Ok(KPAVPixelFormat(pix_fmt))
+    }
+
     pub fn get(&self) -> AVPixelFormat {
         self.0
     }
@@ -553,6 +561,14 @@ impl KPAVSampleFormat {
         KPAVSampleFormat(sample_format)
     }
 
+    pub fn from_name(name: &str) -> Result<Self> {
+        let sample_format = unsafe { av_get_sample_fmt(cstring!(name).as_ptr()) };
+        if sample_format == AV_SAMPLE_FMT_NONE {
+            return Err(anyhow!("unknown sample format. name: {}", name));
+        }
+        Ok(KPAVSampleFormat(sample_format))
+    }
+
     pub fn get(&self) -> AVSampleFormat {
         self.0
     }
@@ -573,6 +589,19 @@ impl KPAVCodecId {
     pub const fn from(codec_id: AVCodecID) -> Self {
         KPAVCodecId(codec_id)
     }
+
+    // accept an encoder name like libx264 or a codec name like h264
+    pub fn from_name(name: &str) -> Result<Self> {
+        let encoder = unsafe { avcodec_find_encoder_by_name(cstring!(name).as_ptr()) };
+        if !encoder.is_null() {
+            return Ok(KPAVCodecId(unsafe { (*encoder).id }));
+        }
+        let descriptor = unsafe { avcodec_descriptor_get_by_name(cstring!(name).as_ptr()) };
+        if descriptor.is_null() {
+            return Err(anyhow!("unknown codec. name: {}", name));
+        }
+        Ok(KPAVCodecId(unsafe { (*descriptor).id }))
+    }
     pub fn get(&self) -> AVCodecID {
         self.0
     }
@@ -2,14 +2,34 @@ use crate::util::*;
 
 #[derive(Debug, Display, EnumString, Clone)]
 pub enum KPEncodeParameterProfile {
+    #[strum(serialize = "baseline")]
+    Baseline,
+    #[strum(serialize = "main")]
+    Main,
     #[strum(serialize = "high")]
     High,
 }
 
 #[derive(Debug, Display, EnumString, Clone)]
 pub enum KPEncodeParameterPreset {
+    #[strum(serialize = "ultrafast")]
+    UltraFast,
+    #[strum(serialize = "superfast")]
+    SuperFast,
     #[strum(serialize = "veryfast")]
     VeryFast,
+    #[strum(serialize = "faster")]
+    Faster,
+    #[strum(serialize = "fast")]
+    Fast,
+    #[strum(serialize = "medium")]
+    Medium,
+    #[strum(serialize = "slow")]
+    Slow,
+    #[strum(serialize = "slower")]
+    Slower,
+    #[strum(serialize = "veryslow")]
+    VerySlow,
 }
 
 #[derive(Debug, Clone)]
@@ -92,4 +112,97 @@ impl KPEncodeParameter {
             }
         }
     }
-}
\ No newline at end of file
+
+    pub fn get_channel_layout(channels: usize) -> usize {
+        unsafe { av_get_default_channel_layout(channels as c_int) as usize }
+    }
+
+    // check the encoder exists, the output format can mux it and it accepts the formats
+    pub fn validate<T: ToString>(&self, output_format: T) -> Result<()> {
+        let output_format = output_format.to_string();
+        let format = unsafe { av_guess_format(cstring!(output_format).as_ptr(), ptr::null_mut(), ptr::null_mut()) };
+        if format.is_null() {
+            return Err(anyhow!("output format not found. format: {}", output_format));
+        }
+
+        let codec_id = match self {
+            KPEncodeParameter::Video { codec_id, .. } => codec_id,
+            KPEncodeParameter::Audio { codec_id, .. } => codec_id,
+        };
+        let codec = unsafe { avcodec_find_encoder(codec_id.get()) };
+        if codec.is_null() {
+            return Err(anyhow!("encoder not found. codec: {}", codec_id));
+        }
+        let ret = unsafe { avformat_query_codec(format, codec_id.get(), FF_COMPLIANCE_NORMAL as c_int) };
+        if ret != 1 {
+            return Err(anyhow!("output format not support codec. format: {}, codec: {}", output_format, codec_id));
+        }
+
+        let codec = unsafe { *codec };
+        match self {
+            KPEncodeParameter::Video { pix_fmt, .. } => {
+                if !codec.pix_fmts.is_null() && !Self::supported(codec.pix_fmts, AV_PIX_FMT_NONE, pix_fmt.get()) {
+                    return Err(anyhow!("encoder not support pixel format. codec: {}, pix_fmt: {}", codec_id, pix_fmt));
+                }
+            }
+            KPEncodeParameter::Audio { sample_rate, sample_fmt, .. } => {
+                if !codec.sample_fmts.is_null() && !Self::supported(codec.sample_fmts, AV_SAMPLE_FMT_NONE, sample_fmt.get()) {
+                    return Err(anyhow!("encoder not support sample format. codec: {}, sample_fmt: {}", codec_id, sample_fmt));
+                }
+                if !codec.supported_samplerates.is_null() && !Self::supported(codec.supported_samplerates, 0, sample_rate.clone() as c_int) {
+                    return Err(anyhow!("encoder not support sample rate. codec: {}, sample_rate: {}", codec_id, sample_rate));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    // iterate a terminated list returned by ffmpeg
+    fn supported<T: PartialEq + Copy>(list: *const T, terminator: T, value: T) -> bool {
+        let mut index = 0;
+        loop {
+            let item = unsafe { *list.add(index) };
+            if item == terminator {
+                return false;
+            }
+            if item == value {
+                return true;
+            }
+            index += 1;
+        }
+    }
+}
+
+#[test]
+fn validate_parameter() -> Result<()> {
+    let video = KPEncodeParameter::default(&KPAVMediaType::KPAVMEDIA_TYPE_VIDEO);
+    video.validate("flv")?;
+    let audio = KPEncodeParameter::default(&KPAVMediaType::KPAVMEDIA_TYPE_AUDIO);
+    audio.validate("flv")?;
+
+    let audio = KPEncodeParameter::Audio {
+        codec_id: KPAVCodecId::from(AV_CODEC_ID_AAC),
+        sample_rate: 48000,
+        sample_fmt: KPAVSampleFormat::from(AV_SAMPLE_FMT_U8),
+        channel_layout: KPEncodeParameter::get_channel_layout(2),
+        channels: 2,
+        metadata: BTreeMap::new(),
+    };
+    assert!(audio.validate("flv").is_err());
+
+    let video = KPEncodeParameter::Video {
+        codec_id: KPAVCodecId::from_name("mpeg2video")?,
+        width: 848,
+        height: 480,
+        pix_fmt: KPAVPixelFormat::from_name("yuv420p")?,
+        framerate: KPAVRational::from_fps(25),
+        max_bitrate: 0,
+        quality: 0,
+        profile: KPEncodeParameterProfile::High,
+        preset: KPEncodeParameterPreset::VeryFast,
+        gop_uint: 2,
+        metadata: BTreeMap::new(),
+    };
+    assert!(video.validate("flv").is_err());
+    Ok(())
+}
@@ -0,0 +1,27 @@
+use once_cell::sync::Lazy;
+use prometheus::{register_gauge, register_int_counter, register_int_counter_vec, register_int_gauge_vec, Gauge, IntCounter, IntCounterVec, IntGaugeVec};
+
+pub struct KPCodecMetrics {
+    pub decode_frames: IntCounterVec,
+    pub encode_frames: IntCounterVec,
+    pub encode_packet_queue: IntGaugeVec,
+    pub output_bytes: IntCounter,
+    pub output_bitrate: Gauge,
+    pub pacing_lag: Gauge,
+}
+
+// registered on the prometheus default registry, gathered by the application
+pub static METRICS: Lazy<KPCodecMetrics> = Lazy::new(|| {
+    KPCodecMetrics {
+        decode_frames: register_int_counter_vec!("kplayer_decode_frames_total", "Frames decoded per media type", &["media_type"]).unwrap(),
+        encode_frames: register_int_counter_vec!("kplayer_encode_frames_total", "Frames sent to the encoder per media type", &["media_type"]).unwrap(),
+        encode_packet_queue: register_int_gauge_vec!("kplayer_encode_packet_queue", "Encoded packets waiting in the stream queue per media type", &["media_type"]).unwrap(),
+        output_bytes: register_int_counter!("kplayer_output_bytes_total", "Bytes written to the output").unwrap(),
+        output_bitrate: register_gauge!("kplayer_output_bitrate", "Output bitrate in bits per second").unwrap(),
+        pacing_lag: register_gauge!("kplayer_pacing_lag_seconds", "Lag of the live pacing clock behind wall time in seconds").unwrap(),
+    }
+});
+
+pub fn register() {
+    Lazy::force(&METRICS);
+}
@@ -13,4 +13,5 @@ use crate::{averror, cstr, cstring};
 
 pub mod alias;
 pub mod codec_status;
-pub mod encode_parameter;
\ No newline at end of file
+pub mod encode_parameter;
+pub mod metrics;
\ No newline at end of file
@@ -4,6 +4,7 @@ use streamhub::notify::Notifier;
 use async_trait::async_trait;
 use crate::notify::notifier::KPServerNotifier;
 use crate::util::message::KPServerMessage;
+use crate::util::metrics::METRICS;
 
 pub struct KPEventNotifier {
     notifier: Arc<dyn KPServerNotifier>,
@@ -13,24 +14,28 @@ pub struct KPEventNotifier {
 impl Notifier for KPEventNotifier {
     async fn on_publish_notify(&self, event: &StreamHubEventMessage) {
         if let StreamHubEventMessage::Publish { identifier, info } = event.clone() {
+            METRICS.publishers.inc();
             self.notifier.notify(&KPServerMessage::Publish { identifier, info }).await;
         }
     }
 
     async fn on_unpublish_notify(&self, event: &StreamHubEventMessage) {
         if let StreamHubEventMessage::UnPublish { identifier, info } = event.clone() {
+            METRICS.publishers.dec();
             self.notifier.notify(&KPServerMessage::Unpublish { identifier, info }).await;
         }
     }
 
     async fn on_play_notify(&self, event: &StreamHubEventMessage) {
         if let StreamHubEventMessage::Subscribe { identifier, info } = event.clone() {
+            METRICS.subscribers.inc();
             self.notifier.notify(&KPServerMessage::Subscribe { identifier, info }).await;
         }
     }
 
     async fn on_stop_notify(&self, event: &StreamHubEventMessage) {
         if let StreamHubEventMessage::UnSubscribe { identifier, info } = event.clone() {
+            METRICS.subscribers.dec();
             self.notifier.notify(&KPServerMessage::Unsubscribe { identifier, info }).await;
         }
     }
@@ -2,13 +2,14 @@ use log::info;
 use async_trait::async_trait;
 use crate::notify::notifier::KPServerNotifier;
 use crate::util::message::KPServerMessage;
+use crate::util::secret::redact;
 
 pub struct KPLogNotifier {}
 
 #[async_trait]
 impl KPServerNotifier for KPLogNotifier {
     async fn notify(&self, event: &KPServerMessage) {
-        info!("server event: {:?}", event);
+        info!("server event: {}", redact(format!("{:?}", event)));
     }
 }
 
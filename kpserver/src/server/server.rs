@@ -6,6 +6,7 @@ use std::time::Duration;
 use tokio::{join, select};
 use tokio::net::TcpStream;
 use tokio::sync::broadcast::error::RecvError;
+use tokio::task::JoinHandle;
 use tokio::time::sleep;
 use hls::errors::HlsError;
 use hls::remuxer::HlsRemuxer;
@@ -14,18 +15,30 @@ use streamhub::define::{BroadcastEvent, StreamHubEventSender};
 use streamhub::stream::StreamIdentifier;
 use crate::server::*;
 use crate::util::parse_url::get_url_info;
+use crate::util::metrics::METRICS;
+use crate::util::secret::{redact, redact_url};
 
 pub struct KPServer {
     service: Arc<KPService>,
+    handles: Vec<JoinHandle<()>>,
 }
 
 impl KPServer {
     pub fn new(service: Arc<KPService>) -> Self {
         KPServer {
             service,
+            handles: Vec::new(),
         }
     }
 
+    // abort every listener, remuxer, pull and push task started by initialize
+    pub fn stop(&mut self) {
+        for handle in self.handles.drain(..) {
+            handle.abort();
+        }
+        info!("server stopped");
+    }
+
     pub async fn initialize(&mut self) {
         let stream_hub = self.service.stream_hub.clone();
         let notifier = self.service.notifier.clone();
@@ -37,7 +50,7 @@ impl KPServer {
                     let port_clone = port.clone();
                     let name_clone = name.clone();
 
-                    tokio::spawn(async move {
+                    self.handles.push(tokio::spawn(async move {
                         let error = match httpflv::server::run(producer, port_clone, None).await {
                             Ok(_) => None,
                             Err(err) => {
@@ -46,7 +59,7 @@ impl KPServer {
                             }
                         };
                         notifier_sender.notify(&KPServerMessage::HttpflvStop { name: name_clone, error }).await;
-                    });
+                    }));
 
                     debug!("http-flv server listen on {}, name: {}", port, name);
                     notifier.notify(&KPServerMessage::HttpflvStart { name: name.clone() }).await;
@@ -59,7 +72,7 @@ impl KPServer {
 
                     let msg_notifier_remuxer = notifier.clone();
                     let name_remuxer_clone = name.clone();
-                    tokio::spawn(async move {
+                    self.handles.push(tokio::spawn(async move {
                         let error = match hls_remuxer.run().await {
                             Ok(_) => None,
                             Err(err) => {
@@ -68,12 +81,12 @@ impl KPServer {
                             }
                         };
                         msg_notifier_remuxer.notify(&KPServerMessage::HlsStop { name: name_remuxer_clone, error }).await;
-                    });
+                    }));
                     notifier.notify(&KPServerMessage::HlsStart { name: name.clone() }).await;
 
                     let msg_notifier_server = notifier.clone();
                     let name_server_clone = name.clone();
-                    tokio::spawn(async move {
+                    self.handles.push(tokio::spawn(async move {
                         let error = match hls::server::run(port_clone, None).await {
                             Ok(_) => None,
                             Err(err) => {
@@ -82,7 +95,7 @@ impl KPServer {
                             }
                         };
                         msg_notifier_server.notify(&KPServerMessage::HlsStop { name: name_server_clone, error }).await;
-                    });
+                    }));
 
                     stream_hub.lock().await.set_hls_enabled(true);
                     debug!("hls server listen on {},name: {}", port, name);
@@ -95,7 +108,7 @@ impl KPServer {
                     let msg_notifier = notifier.clone();
                     let name_clone = name.clone();
 
-                    tokio::spawn(async move {
+                    self.handles.push(tokio::spawn(async move {
                         let error = match rtmp_server.run().await {
                             Ok(_) => None,
                             Err(err) => {
@@ -104,7 +117,7 @@ impl KPServer {
                             }
                         };
                         msg_notifier.notify(&KPServerMessage::RtmpStop { name: name_clone, error }).await;
-                    });
+                    }));
 
                     debug!("rtmp server listen on {}, name: {}", bind_address, name);
                     notifier.notify(&KPServerMessage::RtmpStart { name, address, port }).await;
@@ -116,7 +129,7 @@ impl KPServer {
                     let mut event_consumer = self.service.stream_hub.lock().await.get_client_event_consumer();
                     let msg_notifier = notifier.clone();
 
-                    tokio::spawn(async move {
+                    self.handles.push(tokio::spawn(async move {
                         if !keep_alive {
                             loop {
                                 match event_consumer.recv().await {
@@ -128,7 +141,7 @@ impl KPServer {
                                             debug!("receive pull event. app_name: {}, stream_name: {}", app_name, stream_name);
 
                                             if source_app_name == app_name && source_stream_name == stream_name {
-                                                info!("receive pull event, will open source url. source_url: {}, app_name: {}, stream_name: {}", source_address, app_name, stream_name);
+                                                info!("receive pull event, will open source url. source_url: {}, app_name: {}, stream_name: {}", redact_url(&source_address), app_name, stream_name);
                                                 break;
                                             }
                                         }
@@ -147,7 +160,7 @@ impl KPServer {
 
                             msg_notifier.notify(&KPServerMessage::RtmpPullStart {
                                 name: name.clone(),
-                                source_url: source_url.clone(),
+                                source_url: redact_url(&source_url),
                                 retry_interval: retry_interval.clone(),
                                 retry_count: match retry_interval {
                                     None => None,
@@ -157,10 +170,10 @@ impl KPServer {
 
                             // connect pull from source
                             if let Err(err) = KPServer::create_pull(producer, &source_url, target_app_name.clone(), target_stream_name.clone(), timeout).await {
-                                error!("rtmp pull failed. source_url: {}, error: {}", source_url, err);
+                                error!("rtmp pull failed. source_url: {}, error: {}", redact_url(&source_url), redact(&err));
                                 msg_notifier.notify(&KPServerMessage::RtmpPullStop {
                                     name: name.clone(),
-                                    source: source_url.clone(),
+                                    source_url: redact_url(&source_url),
                                     error: Some(err.to_string()),
                                 }).await;
                             }
@@ -173,13 +186,13 @@ impl KPServer {
                             } else {
                                 msg_notifier.notify(&KPServerMessage::RtmpPullStop {
                                     name: name.clone(),
-                                    source: source_url.clone(),
+                                    source_url: redact_url(&source_url),
                                     error: None,
                                 }).await;
                                 break;
                             }
                         }
-                    });
+                    }));
 
                     self.service.stream_hub.lock().await.set_rtmp_pull_enabled(true);
                 }
@@ -190,7 +203,7 @@ impl KPServer {
                     let mut event_consumer = self.service.stream_hub.lock().await.get_client_event_consumer();
                     let msg_notifier = notifier.clone();
 
-                    tokio::spawn(async move {
+                    self.handles.push(tokio::spawn(async move {
                         if let Some(t) = timeout.clone() {
                             if let Err(err) = tokio::time::timeout(t, async {
                                 while let Ok(event) = event_consumer.recv().await {
@@ -205,29 +218,52 @@ impl KPServer {
                             }).await {
                                 msg_notifier.notify(&KPServerMessage::RtmpPushStop {
                                     name: name.clone(),
-                                    sink_url: sink_url.clone(),
+                                    sink_url: redact_url(&sink_url),
                                     error: Some(format!("find source resource timeout, error: {}", err)),
                                 }).await;
                             }
                         };
 
-                        msg_notifier.notify(&KPServerMessage::RtmpPushStart {
-                            name: name.clone(),
-                            sink_url: sink_url.clone(),
-                        }).await;
+                        // each push retries on its own, a failed sink never affects the others
+                        let mut retry_c = 1usize;
+                        loop {
+                            msg_notifier.notify(&KPServerMessage::RtmpPushStart {
+                                name: name.clone(),
+                                sink_url: redact_url(&sink_url),
+                                retry_interval: retry_interval.clone(),
+                                retry_count: match retry_interval {
+                                    None => None,
+                                    Some(_) => Some(retry_c.clone()),
+                                },
+                            }).await;
+
+                            // create push
+                            let error = match KPServer::create_push(producer.clone(), sink_url.clone(), target_app_name.clone(), target_stream_name.clone(), timeout).await {
+                                Ok(_) => None,
+                                Err(err) => {
+                                    error!("rtmp push failed. sink_url: {}, error: {}", redact_url(&sink_url), redact(&err));
+                                    Some(err.to_string())
+                                }
+                            };
 
-                        // create push
-                        let error = match KPServer::create_push(producer.clone(), sink_url.clone(), target_app_name, target_stream_name, timeout).await {
-                            Ok(_) => None,
-                            Err(err) => Some(err.to_string()),
-                        };
+                            msg_notifier.notify(&KPServerMessage::RtmpPushStop {
+                                name: name.clone(),
+                                sink_url: redact_url(&sink_url),
+                                error,
+                            }).await;
 
-                        msg_notifier.notify(&KPServerMessage::RtmpPushStop {
-                            name: name.clone(),
-                            sink_url: sink_url.clone(),
-                            error,
-                        }).await;
-                    });
+                            // reconnect
+                            match retry_interval {
+                                Some(d) => {
+                                    info!("rtmp push retry on {:?} after reconnect, retry count: {}", d, retry_c);
+                                    sleep(d.clone()).await;
+                                    METRICS.push_reconnects.with_label_values(&[&name]).inc();
+                                    retry_c += 1;
+                                }
+                                None => break,
+                            }
+                        }
+                    }));
 
                     self.service.stream_hub.lock().await.set_rtmp_push_enabled(true);
                 }
@@ -239,7 +275,7 @@ impl KPServer {
         let (source_address, source_app_name, source_stream_name) = get_url_info(&source_url)?;
 
         let stream = TcpStream::connect(source_address.clone()).await?;
-        debug!("connect source url connection. source_url: {}", source_url);
+        debug!("connect source url connection. source_url: {}", redact_url(source_url));
 
         let mut client_session = ClientSession::new(
             stream,
@@ -267,7 +303,7 @@ impl KPServer {
         let (sink_address, sink_app_name, sink_stream_name) = get_url_info(&sink_url)?;
 
         let stream = TcpStream::connect(sink_address.clone()).await?;
-        debug!("connect sink url connection. source_url: {}", sink_url);
+        debug!("connect sink url connection. source_url: {}", redact_url(&sink_url));
 
         let mut client_session = ClientSession::new(
             stream,
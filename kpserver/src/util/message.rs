@@ -1,10 +1,13 @@
+use std::fmt::Debug;
 use std::net::IpAddr;
 use std::time::Duration;
+use serde::{Serialize, Serializer};
 use strum_macros::Display;
 use streamhub::define::{PublisherInfo, SubscriberInfo};
 use streamhub::stream::StreamIdentifier;
 
-#[derive(Clone, Display, Debug)]
+#[derive(Clone, Display, Debug, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
 pub enum KPServerMessage {
     RtmpStart {
         name: String,
@@ -32,17 +35,21 @@ pub enum KPServerMessage {
     RtmpPullStart {
         name: String,
         source_url: String,
+        #[serde(serialize_with = "serialize_option_seconds")]
         retry_interval: Option<Duration>,
         retry_count: Option<usize>,
     },
     RtmpPullStop {
         name: String,
-        source: String,
+        source_url: String,
         error: Option<String>,
     },
     RtmpPushStart {
         name: String,
         sink_url: String,
+        #[serde(serialize_with = "serialize_option_seconds")]
+        retry_interval: Option<Duration>,
+        retry_count: Option<usize>,
     },
     RtmpPushStop {
         name: String,
@@ -50,23 +57,44 @@ pub enum KPServerMessage {
         error: Option<String>,
     },
     Publish {
+        #[serde(serialize_with = "serialize_debug")]
         identifier: StreamIdentifier,
+        #[serde(serialize_with = "serialize_debug")]
         info: PublisherInfo,
     },
     Unpublish {
+        #[serde(serialize_with = "serialize_debug")]
         identifier: StreamIdentifier,
+        #[serde(serialize_with = "serialize_debug")]
         info: PublisherInfo,
     },
     Subscribe {
+        #[serde(serialize_with = "serialize_debug")]
         identifier: StreamIdentifier,
+        #[serde(serialize_with = "serialize_debug")]
         info: SubscriberInfo,
     },
     Unsubscribe {
+        #[serde(serialize_with = "serialize_debug")]
         identifier: StreamIdentifier,
+        #[serde(serialize_with = "serialize_debug")]
         info: SubscriberInfo,
     },
     Unknown {
         name: String,
         error: String,
     },
+}
+
+// streamhub types only promise Debug, publish them as their debug text
+fn serialize_debug<T: Debug, S: Serializer>(value: &T, serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.serialize_str(&format!("{:?}", value))
+}
+
+// seconds as f64, the same as the transcode events
+fn serialize_option_seconds<S: Serializer>(value: &Option<Duration>, serializer: S) -> Result<S::Ok, S::Error> {
+    match value {
+        Some(duration) => serializer.serialize_f64(duration.as_secs_f64()),
+        None => serializer.serialize_none(),
+    }
 }
\ No newline at end of file
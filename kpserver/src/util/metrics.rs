@@ -0,0 +1,21 @@
+use once_cell::sync::Lazy;
+use prometheus::{register_int_counter_vec, register_int_gauge, IntCounterVec, IntGauge};
+
+pub struct KPServerMetrics {
+    pub push_reconnects: IntCounterVec,
+    pub publishers: IntGauge,
+    pub subscribers: IntGauge,
+}
+
+// registered on the prometheus default registry, gathered by the application
+pub static METRICS: Lazy<KPServerMetrics> = Lazy::new(|| {
+    KPServerMetrics {
+        push_reconnects: register_int_counter_vec!("kplayer_server_push_reconnects_total", "Reconnects of rtmp push per sink", &["name"]).unwrap(),
+        publishers: register_int_gauge!("kplayer_server_publishers", "Streams currently published to the server").unwrap(),
+        subscribers: register_int_gauge!("kplayer_server_subscribers", "Subscribers currently playing from the server").unwrap(),
+    }
+});
+
+pub fn register() {
+    Lazy::force(&METRICS);
+}
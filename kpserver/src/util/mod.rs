@@ -10,4 +10,6 @@ pub mod service;
 pub mod config;
 pub mod message;
 pub mod const_var;
-pub mod parse_url;
\ No newline at end of file
+pub mod parse_url;
+pub mod metrics;
+pub mod secret;
\ No newline at end of file
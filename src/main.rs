@@ -1,29 +1,37 @@
-use std::collections::BTreeMap;
 use std::env;
-use std::net::IpAddr;
 use std::path::PathBuf;
-use std::str::FromStr;
 use std::sync::Arc;
 use std::time::Duration;
-use kpapp::cmd::cli::{cli, CONFIG_PATH_FLAG, HOMEDIR_FLAG, LOGLEVEL_FLAG};
+use kpapp::cmd::cli::{cli, CONFIG_PATH_FLAG, FILE_ARG, HOMEDIR_FLAG, IGNORE_STATE_FLAG, JSON_FLAG, LOGLEVEL_FLAG, PLUGIN_COMMAND, PLUGIN_INFO_COMMAND, PROBE_COMMAND, VALIDATE_COMMAND, CONFIG_COMMAND, CONFIG_SCHEMA_COMMAND};
+use kpapp::cmd::print_output;
+use kpapp::cmd::plugin::plugin_info;
+use kpapp::cmd::probe::probe;
+use kpapp::cmd::schema::config_schema;
+use kpapp::cmd::validate::validate;
 use anyhow::{anyhow, Result};
-use log::{debug, error, info, Log};
+use log::{debug, error, info, warn, Log};
 use tokio::sync::mpsc::Sender;
+use tokio::sync::broadcast::error::RecvError;
+use tokio::sync::watch;
 use kpapp::app::app::KPApp;
+use kpapp::app::reload::KPAppReloader;
+use kpapp::api::metrics::KPAppMetricsServer;
+use kpapp::api::server::KPAppApiServer;
+use kpapp::api::state::KPAppApiState;
 use kpapp::util::context::KPAppContext;
-use kpcodec::util::alias::KPAVMediaType;
-use kpcodec::util::encode_parameter::KPEncodeParameter;
 use kpserver::server::server::KPServer;
 use kpserver::util::service::KPService;
 use crate::init::initialize;
 use crate::util::event::{KPEventLoop, KPEventMessage};
 use crate::util::server_event::KPServerEvent;
+use crate::util::app_event::KPAppEvent;
+use crate::util::signal::wait_signal;
 use kpserver::util::message::KPServerMessage;
 
 mod init;
 mod util;
 
-const DEFAULT_CONFIG_FILENAME: &str = "kplayer.json";
+const DEFAULT_CONFIG_FILENAMES: &[&str] = &["kplayer.json", "kplayer.yaml", "kplayer.yml", "kplayer.toml"];
 
 #[tokio::main]
 async fn main() {
@@ -39,7 +47,7 @@ async fn main() {
     if !homedir.exists() { error!("No such directory: {}", homedir.display()); }
 
     let config_path = match matches.get_one::<String>(CONFIG_PATH_FLAG) {
-        None => homedir.join(DEFAULT_CONFIG_FILENAME),
+        None => DEFAULT_CONFIG_FILENAMES.iter().map(|name| homedir.join(name)).find(|path| path.exists()).unwrap_or(homedir.join(DEFAULT_CONFIG_FILENAMES[0])),
         Some(p) => { PathBuf::from(p) }
     };
     if !config_path.exists() { error!("No such config file: {}", config_path.display()); }
@@ -49,106 +57,274 @@ async fn main() {
     // initialize
     initialize(Some(log_level.clone()));
 
-    // create context
-    let context = match KPAppContext::new(homedir.clone(), config_path.clone()) {
-        Ok(c) => c,
-        Err(err) => {
-            error!("initialize app context failed. error: {}", err);
-            return;
-        }
-    };
-    debug!("load config file success. homedir: {}, config_path: {}", homedir.display(), config_path.display());
-
     // command exec
     match matches.subcommand() {
+        Some((VALIDATE_COMMAND, sub_matches)) => {
+            let report = validate(homedir, config_path).await;
+            if let Err(err) = print_output(&report, sub_matches.get_flag(JSON_FLAG)) {
+                error!("print validate report failed. error: {}", err);
+            }
+            if !report.valid { std::process::exit(1); }
+        }
+        Some((PROBE_COMMAND, sub_matches)) => {
+            let file = sub_matches.get_one::<String>(FILE_ARG).unwrap();
+            let result = probe(file).and_then(|info| print_output(&info, sub_matches.get_flag(JSON_FLAG)));
+            if let Err(err) = result {
+                error!("probe failed. error: {}", err);
+                std::process::exit(1);
+            }
+        }
+        Some((PLUGIN_COMMAND, sub_matches)) => {
+            if let Some((PLUGIN_INFO_COMMAND, info_matches)) = sub_matches.subcommand() {
+                let file = PathBuf::from(info_matches.get_one::<String>(FILE_ARG).unwrap());
+                let result = match plugin_info(file).await {
+                    Ok(info) => print_output(&info, info_matches.get_flag(JSON_FLAG)),
+                    Err(err) => Err(err),
+                };
+                if let Err(err) = result {
+                    error!("plugin info failed. error: {}", err);
+                    std::process::exit(1);
+                }
+            }
+        }
+        Some((CONFIG_COMMAND, sub_matches)) => {
+            if let Some((CONFIG_SCHEMA_COMMAND, _)) = sub_matches.subcommand() {
+                let result = config_schema(homedir.clone()).await.and_then(|schema| Ok(serde_json::to_string_pretty(&schema)?));
+                match result {
+                    Ok(schema) => println!("{}", schema),
+                    Err(err) => {
+                        error!("generate config schema failed. error: {}", err);
+                        std::process::exit(1);
+                    }
+                }
+            }
+        }
         Some(_) => {}
         None => {
+            // create context
+            let mut context = match KPAppContext::new(homedir.clone(), config_path.clone()) {
+                Ok(c) => c,
+                Err(err) => {
+                    error!("initialize app context failed. error: {}", err);
+                    return;
+                }
+            };
+            debug!("load config file success. homedir: {}, config_path: {}", homedir.display(), config_path.display());
+            context.ignore_state = matches.get_flag(IGNORE_STATE_FLAG);
+
             let event_loop = KPEventLoop::new();
 
+            // start metrics
+            if let Some(metrics_config) = context.config.metrics.clone() {
+                tokio::spawn(async move {
+                    if let Err(err) = KPAppMetricsServer::new(metrics_config).run().await {
+                        error!("metrics server exit failed. error: {}", err);
+                    }
+                });
+            }
+
             // start server
+            let (server_shutdown_sender, server_shutdown_receiver) = watch::channel(false);
             let server_context_clone = context.clone();
             let server_sender_clone = event_loop.get_sender();
-            tokio::spawn(async move {
-                start_server(server_sender_clone.clone(), server_context_clone).await
+            let server_handle = tokio::spawn(async move {
+                start_server(server_sender_clone.clone(), server_context_clone, server_shutdown_receiver).await
             });
 
             // start transcode
+            let (transcode_shutdown_sender, transcode_shutdown_receiver) = watch::channel(false);
             let transcode_context_clone = context.clone();
             let transcode_sender_clone = event_loop.get_sender();
             let transcode_broadcast_receiver = event_loop.subscribe();
-            tokio::task::spawn_blocking(move || {
-                futures::executor::block_on(async move {
-                    start_transcode(transcode_sender_clone.clone(), transcode_broadcast_receiver, transcode_context_clone).await
-                });
+            // driven by the runtime handle, the scene blocks on its own executor for plugin updates
+            let runtime = tokio::runtime::Handle::current();
+            let mut transcode_handle = tokio::task::spawn_blocking(move || {
+                runtime.block_on(async move {
+                    start_transcode(transcode_sender_clone.clone(), transcode_broadcast_receiver, transcode_context_clone, transcode_shutdown_receiver).await
+                })
             });
 
             // event loop
-            event_loop.event_loop().await;
+            tokio::spawn(event_loop.event_loop());
+
+            // a finished playlist or an aborted transcode stops the process as a signal does
+            let transcode_result = tokio::select! {
+                result = wait_signal() => {
+                    if let Err(err) = result {
+                        error!("wait shutdown signal failed. error: {}", err);
+                        return;
+                    }
+                    None
+                }
+                result = &mut transcode_handle => Some(result),
+            };
+            let timeout = context.config.shutdown.timeout;
+            match transcode_result {
+                None => info!("shutdown signal received, stopping. timeout: {:?}", timeout),
+                Some(_) => info!("transcode exited, stopping. timeout: {:?}", timeout),
+            }
+
+            // stop transcode first so the output trailer is written, then the servers
+            let shutdown = async move {
+                let result = match transcode_result {
+                    Some(result) => result,
+                    None => {
+                        let _ = transcode_shutdown_sender.send(true);
+                        transcode_handle.await
+                    }
+                };
+                let success = match result {
+                    Ok(Ok(_)) => true,
+                    Ok(Err(err)) => {
+                        error!("transcode app exit failed. error: {}", err);
+                        false
+                    }
+                    Err(err) => {
+                        error!("wait transcode exit failed. error: {}", err);
+                        false
+                    }
+                };
+                let _ = server_shutdown_sender.send(true);
+                if let Err(err) = server_handle.await {
+                    error!("wait server exit failed. error: {}", err);
+                }
+                success
+            };
+            tokio::select! {
+                success = shutdown => {
+                    info!("shutdown success");
+                    if !success {
+                        std::process::exit(1);
+                    }
+                }
+                _ = tokio::time::sleep(timeout) => {
+                    error!("shutdown timeout, force exit. timeout: {:?}", timeout);
+                    std::process::exit(1);
+                }
+                _ = wait_signal() => {
+                    warn!("second shutdown signal received, force exit");
+                    std::process::exit(1);
+                }
+            }
         }
     }
 }
 
-async fn start_server(sender: Sender<KPEventMessage>, context: KPAppContext) {
+async fn start_server(sender: Sender<KPEventMessage>, context: KPAppContext, mut shutdown: watch::Receiver<bool>) {
     let notifier = KPServerEvent::new(sender.clone());
     let mut service = KPService::new(Arc::new(notifier));
-    let output = context.config.output.clone();
-
-    service.append(kpserver::util::config::KPConfig::rtmp_push {
-        name: output.name.clone(),
-        app_name: context.temporarily_server_app.clone(),
-        stream_name: output.name.clone(),
-        sink_url: output.path.clone(),
-        timeout: Some(Duration::from_secs(10)),
-        retry_interval: Some(Duration::from_secs(5)),
-    });
+    for output in context.config.output.iter() {
+        service.append(kpserver::util::config::KPConfig::rtmp_push {
+            name: output.name.clone(),
+            app_name: context.temporarily_server_app.clone(),
+            stream_name: context.get_temporarily_server_stream(),
+            sink_url: output.path.clone(),
+            timeout: output.timeout,
+            retry_interval: output.retry_interval,
+        });
+    }
+    let server = &context.config.server;
     service.append(kpserver::util::config::KPConfig::rtmp {
         name: "core".to_string(),
-        address: IpAddr::from_str("0.0.0.0").unwrap(),
-        port: 1935,
-        gop_number: 29,
+        address: server.address,
+        port: server.port,
+        gop_number: server.gop_number,
     });
+    if let Some(httpflv) = &server.httpflv {
+        service.append(kpserver::util::config::KPConfig::httpflv {
+            name: "httpflv".to_string(),
+            port: httpflv.port,
+        });
+    }
+    if let Some(hls) = &server.hls {
+        service.append(kpserver::util::config::KPConfig::hls {
+            name: "hls".to_string(),
+            port: hls.port,
+        });
+    }
 
     let service_arc = Arc::new(service);
 
     // create server
     let mut server = KPServer::new(service_arc.clone());
     server.initialize().await;
-    service_arc.wait().await;
+    tokio::select! {
+        _ = service_arc.wait() => {}
+        _ = shutdown.changed() => {}
+    }
+    server.stop();
 }
 
-async fn start_transcode(sender: Sender<KPEventMessage>, mut subscriber: tokio::sync::broadcast::Receiver<KPEventMessage>, mut context: KPAppContext) {
-    let mut encode_parameter = BTreeMap::new();
-    encode_parameter.insert(KPAVMediaType::KPAVMEDIA_TYPE_VIDEO, KPEncodeParameter::default(&KPAVMediaType::KPAVMEDIA_TYPE_VIDEO));
-    encode_parameter.insert(KPAVMediaType::KPAVMEDIA_TYPE_AUDIO, KPEncodeParameter::default(&KPAVMediaType::KPAVMEDIA_TYPE_AUDIO));
-    context.config.output.path = format!("rtmp://127.0.0.1:1935/{}/{}", context.temporarily_server_app, context.config.output.name);
-
-    while let Ok(e) = subscriber.recv().await {
-        if let KPEventMessage::server(server_msg) = e {
-            if let KPServerMessage::RtmpStart { name, address, port } = server_msg {
-                if name == "core" {
-                    info!("core rtmp server start. name: {}", name);
-                    break;
+async fn start_transcode(sender: Sender<KPEventMessage>, mut subscriber: tokio::sync::broadcast::Receiver<KPEventMessage>, context: KPAppContext, mut shutdown: watch::Receiver<bool>) -> Result<()> {
+    let encode_parameter = context.config.encode.to_encode_parameter().map_err(|err| anyhow!("create encode parameter failed. error: {}", err))?;
+
+    let wait_core = async {
+        while let Ok(e) = subscriber.recv().await {
+            if let KPEventMessage::server(server_msg) = e {
+                if let KPServerMessage::RtmpStart { name, address, port } = server_msg {
+                    if name == "core" {
+                        info!("core rtmp server start. name: {}", name);
+                        break;
+                    }
                 }
             }
         }
-    }
-
-    let mut app = match KPApp::new(context, encode_parameter) {
-        Ok(app) => {
-            info!("create transcode app success");
-            app
-        }
-        Err(err) => {
-            error!("create transcode app failed. error: {}", err);
-            return;
-        }
     };
-    match app.start().await {
-        Ok(_) => {
-            info!("transcode app exit success");
+    tokio::select! {
+        _ = wait_core => {}
+        _ = shutdown.changed() => {
+            info!("shutdown before transcode start");
+            return Ok(());
         }
-        Err(err) => {
-            error!("transcode app exit failed. error: {}", err);
+    }
+
+    let config = context.config.clone();
+    let reloader = KPAppReloader::new(context.config_path.clone(), context.config.clone());
+    let notifier = KPAppEvent::new(sender.clone());
+    let mut app = KPApp::new(context, encode_parameter, Arc::new(notifier)).map_err(|err| anyhow!("create transcode app failed. error: {}", err))?;
+    info!("create transcode app success");
+
+    // hot reload config
+    reloader.watch(app.get_controller());
+
+    // graceful shutdown
+    let controller = app.get_controller();
+    tokio::spawn(async move {
+        if shutdown.changed().await.is_ok() {
+            controller.shutdown();
         }
-    };
+    });
+
+    // control api
+    if let Some(api_config) = config.api.clone() {
+        let api_state = KPAppApiState::new(app.get_controller(), &config);
+        let event_state = api_state.clone();
+        tokio::spawn(async move {
+            loop {
+                let message = match subscriber.recv().await {
+                    Ok(message) => message,
+                    Err(RecvError::Lagged(_)) => continue,
+                    Err(RecvError::Closed) => break,
+                };
+                if let KPEventMessage::server(server_msg) = &message {
+                    if let Err(err) = event_state.on_server_message(server_msg) {
+                        error!("update api output state failed. error: {}", err);
+                    }
+                }
+                match serde_json::to_value(&message) {
+                    Ok(event) => event_state.publish_event(event),
+                    Err(err) => error!("serialize event message failed. error: {}", err),
+                }
+            }
+        });
+        tokio::spawn(async move {
+            if let Err(err) = KPAppApiServer::new(api_config, api_state).run().await {
+                error!("api server exit failed. error: {}", err);
+            }
+        });
+    }
+
+    app.start().await?;
+    info!("transcode app exit success");
+    Ok(())
 }
\ No newline at end of file
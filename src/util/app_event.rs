@@ -0,0 +1,29 @@
+use async_trait::async_trait;
+use log::warn;
+use tokio::sync::mpsc::Sender;
+use kpapp::notify::notifier::KPAppNotifier;
+use kpapp::util::message::KPAppMessage;
+use crate::util::event::KPEventMessage;
+
+#[derive(Debug, Clone)]
+pub struct KPAppEvent {
+    sender: Sender<KPEventMessage>,
+}
+
+#[async_trait]
+impl KPAppNotifier for KPAppEvent {
+    async fn notify(&self, event: &KPAppMessage) {
+        // the event loop is gone while shutting down, the event is dropped
+        if let Err(err) = self.sender.send(KPEventMessage::transcode(event.clone())).await {
+            warn!("send event message failed. error: {}", err);
+        }
+    }
+}
+
+impl KPAppEvent {
+    pub fn new(sender: Sender<KPEventMessage>) -> Self {
+        KPAppEvent {
+            sender,
+        }
+    }
+}
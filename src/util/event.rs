@@ -1,10 +1,13 @@
 use std::sync::Arc;
-use log::{info, LevelFilter};
+use log::LevelFilter;
 use tokio::sync::mpsc::{Receiver, Sender};
 use kpapp::util::message::KPAppMessage;
 use kpserver::util::message::KPServerMessage;
+use kpserver::util::secret::redact;
+use serde::Serialize;
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "source")]
 pub enum KPEventMessage {
     server(KPServerMessage),
     transcode(KPAppMessage),
@@ -60,10 +63,24 @@ impl KPEventLoop {
                         _ => {}
                     }
 
-                    log::log!(default_msg_level, "{:?}", msg);
+                    log::log!(default_msg_level, "{}", redact(format!("{:?}", msg)));
                 }
                 KPEventMessage::transcode(msg) => {
-                    info!("Received KPAppMessage: {:?}", msg);
+                    let mut default_msg_level = log::Level::Info;
+                    match &msg {
+                        KPAppMessage::ItemFailed { .. } => {
+                            default_msg_level = log::Level::Error;
+                        }
+                        KPAppMessage::AppClosed { error, .. } => {
+                            if error.is_some() { default_msg_level = log::Level::Error; }
+                        }
+                        KPAppMessage::Progress { .. } => {
+                            default_msg_level = log::Level::Debug;
+                        }
+                        _ => {}
+                    }
+
+                    log::log!(default_msg_level, "{}", redact(format!("{:?}", msg)));
                 }
             }
         }
@@ -1,2 +1,4 @@
 pub mod event;
 pub mod server_event;
+pub mod app_event;
+pub mod signal;
\ No newline at end of file
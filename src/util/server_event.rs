@@ -1,4 +1,5 @@
 use async_trait::async_trait;
+use log::warn;
 use tokio::sync::mpsc::Sender;
 use kpserver::notify::notifier::KPServerNotifier;
 use kpserver::util::message::KPServerMessage;
@@ -12,7 +13,10 @@ pub struct KPServerEvent {
 #[async_trait]
 impl KPServerNotifier for KPServerEvent {
     async fn notify(&self, event: &KPServerMessage) {
-        self.sender.send(KPEventMessage::server(event.clone())).await.expect("send event message failed");
+        // the event loop is gone while shutting down, the event is dropped
+        if let Err(err) = self.sender.send(KPEventMessage::server(event.clone())).await {
+            warn!("send event message failed. error: {}", err);
+        }
     }
 }
 
@@ -0,0 +1,13 @@
+use anyhow::{anyhow, Result};
+use tokio::signal::unix::{signal, SignalKind};
+
+// resolves on the next SIGINT or SIGTERM
+pub async fn wait_signal() -> Result<()> {
+    let mut interrupt = signal(SignalKind::interrupt()).map_err(|err| anyhow!("listen interrupt signal failed. error: {}", err))?;
+    let mut terminate = signal(SignalKind::terminate()).map_err(|err| anyhow!("listen terminate signal failed. error: {}", err))?;
+    tokio::select! {
+        _ = interrupt.recv() => {}
+        _ = terminate.recv() => {}
+    }
+    Ok(())
+}